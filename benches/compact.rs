@@ -0,0 +1,75 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use serde_json::json;
+
+use jsonstat::json_stat_compact::{compact_stat_with_pool, CompactStat, StringPool};
+use jsonstat::json_stat_extractor::{extract_stat_from_json_value, JsonStat};
+
+// the bytes currently allocated, to measure what a batch of stats keeps in memory
+struct CountingAllocator;
+
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        return System.alloc(layout);
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const BATCH_SIZE: usize = 1_000;
+
+fn batch_of_stats() -> Vec<JsonStat> {
+    return (0..BATCH_SIZE)
+        .map(|index| {
+            extract_stat_from_json_value(json!({
+                "id": index,
+                "name": format!("name_{index}"),
+                "address": {"city": "Paris", "zip": "75001"},
+                "orders": [{"id": 1, "amount": 12.5}, {"id": 2, "amount": 7}]
+            }))
+        })
+        .collect();
+}
+
+fn compact_batch(stats: Vec<JsonStat>) -> Vec<CompactStat> {
+    let mut pool = StringPool::new();
+    return stats
+        .into_iter()
+        .map(|stat| compact_stat_with_pool(stat, &mut pool))
+        .collect();
+}
+
+// live bytes of the batch, measured once and printed next to the criterion timings
+fn bench_memory_usage(c: &mut Criterion) {
+    let allocated_before = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let stats = batch_of_stats();
+    let stats_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_before;
+    let allocated_before = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let compact_stats = compact_batch(batch_of_stats());
+    let compact_stats_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_before;
+    println!(
+        "{BATCH_SIZE} stats: JsonStat {stats_bytes} bytes, CompactStat {compact_stats_bytes} bytes"
+    );
+    drop(compact_stats);
+    c.bench_function("compact_stat_with_pool 1000 stats", |b| {
+        b.iter_batched(
+            || stats.clone(),
+            |stats| compact_batch(black_box(stats)),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, bench_memory_usage);
+criterion_main!(benches);
@@ -0,0 +1,78 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use serde_json::{json, Map, Value};
+
+use jsonstat::json_stat_extractor::{
+    extract_stat_from_json_value, extract_stat_from_ndjson, merge_stats,
+};
+
+fn flat_object(key_count: usize) -> Value {
+    let attributes: Map<String, Value> = (0..key_count)
+        .map(|index| (format!("key_{index}"), json!(format!("value_{index}"))))
+        .collect();
+    return Value::Object(attributes);
+}
+
+fn array_of_objects(item_count: usize) -> Value {
+    let items: Vec<Value> = (0..item_count)
+        .map(
+            |index| json!({"id": index, "name": format!("name_{index}"), "active": index % 2 == 0}),
+        )
+        .collect();
+    return Value::Array(items);
+}
+
+fn ndjson(line_count: usize) -> String {
+    return (0..line_count)
+        .map(|index| json!({"id": index, "name": format!("name_{index}")}).to_string() + "\n")
+        .collect();
+}
+
+fn bench_flat_object(c: &mut Criterion) {
+    let value = flat_object(100);
+    c.bench_function("extract_stat_from_json_value flat object 100 keys", |b| {
+        b.iter_batched(
+            || value.clone(),
+            |value| extract_stat_from_json_value(black_box(value)),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_array_of_objects(c: &mut Criterion) {
+    let value = array_of_objects(10_000);
+    c.bench_function("extract_stat_from_json_value array of 10000 objects", |b| {
+        b.iter_batched(
+            || value.clone(),
+            |value| extract_stat_from_json_value(black_box(value)),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn bench_ndjson(c: &mut Criterion) {
+    let content = ndjson(10_000);
+    c.bench_function("extract_stat_from_ndjson 10000 lines", |b| {
+        b.iter(|| extract_stat_from_ndjson(black_box(content.as_bytes())))
+    });
+}
+
+fn bench_merge_stats(c: &mut Criterion) {
+    let array_stat = extract_stat_from_json_value(array_of_objects(10_000));
+    let other_array_stat = extract_stat_from_json_value(array_of_objects(10_000));
+    c.bench_function("merge_stats two arrays of 10000 objects", |b| {
+        b.iter_batched(
+            || (array_stat.clone(), other_array_stat.clone()),
+            |(array_stat, other_array_stat)| merge_stats(black_box(array_stat), other_array_stat),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_flat_object,
+    bench_array_of_objects,
+    bench_ndjson,
+    bench_merge_stats
+);
+criterion_main!(benches);
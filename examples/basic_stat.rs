@@ -0,0 +1,14 @@
+//! Stat of a JSON document held in memory, printed as the one line summary.
+//!
+//! ```text
+//! cargo run --example basic_stat
+//! ```
+use jsonstat::exporters::summary::json_stat_to_summary_string;
+use jsonstat::json_stat_extractor::extract_stat_from_json_str;
+
+fn main() {
+    let json_stat = extract_stat_from_json_str(
+        r#"[{"id": 1, "name": "first"}, {"id": 2, "name": "second", "tags": ["a"]}]"#,
+    );
+    println!("{}", json_stat_to_summary_string(&json_stat));
+}
@@ -0,0 +1,38 @@
+//! A custom output format written outside of the crate, here a Markdown table of the top level
+//! attributes.
+//!
+//! The exporters of the crate are plain functions from a `JsonStat` to a `String`, a custom one
+//! follows the same shape and reads the stat through its serialized form.
+//!
+//! ```text
+//! cargo run --example custom_exporter
+//! ```
+use jsonstat::json_stat_extractor::{
+    extract_attribute_stats_only, extract_stat_from_json_str, JsonStat,
+};
+
+fn json_stat_to_markdown(json_stat: JsonStat) -> String {
+    let rows: Vec<String> = extract_attribute_stats_only(json_stat)
+        .iter()
+        .map(|attr_stat| {
+            let attr_stat = serde_json::to_value(attr_stat).unwrap();
+            return format!(
+                "| {} | {} | {} |",
+                attr_stat["name"].as_str().unwrap_or_default(),
+                attr_stat["size"],
+                attr_stat["count"]
+            );
+        })
+        .collect();
+    return format!(
+        "| name | size | count |\n| --- | --- | --- |\n{}",
+        rows.join("\n")
+    );
+}
+
+fn main() {
+    let json_stat = extract_stat_from_json_str(
+        r#"[{"id": 1, "name": "first"}, {"id": 2, "name": "second", "tags": ["a"]}]"#,
+    );
+    println!("{}", json_stat_to_markdown(json_stat));
+}
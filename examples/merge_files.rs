@@ -0,0 +1,30 @@
+//! Stat of two JSON files merged as if they were a single document, like the daily exports of
+//! the same API.
+//!
+//! ```text
+//! cargo run --example merge_files -- monday.json tuesday.json
+//! ```
+use std::env::args;
+use std::fs::File;
+use std::io::BufReader;
+
+use jsonstat::json_stat_extractor::{extract_stat_from_json, merge_stats, JsonStat};
+
+fn extract_file_stat(file_name: &str) -> JsonStat {
+    let file =
+        File::open(file_name).unwrap_or_else(|error| panic!("cannot open {file_name}: {error}"));
+    return extract_stat_from_json(BufReader::new(file));
+}
+
+fn main() {
+    let file_names: Vec<String> = args().skip(1).collect();
+    let [file_name, other_file_name] = file_names.as_slice() else {
+        eprintln!("usage: merge_files <file> <other file>");
+        return;
+    };
+    let merged_stat = merge_stats(
+        extract_file_stat(file_name),
+        extract_file_stat(other_file_name),
+    );
+    println!("{merged_stat}");
+}
@@ -0,0 +1,17 @@
+#![no_main]
+
+use jsonstat::json_stat_extractor::{
+    try_extract_stat_from_json_with_options, JsonStatOptions, SizeUnit,
+};
+use libfuzzer_sys::fuzz_target;
+
+// any byte stream either gives a stat or an error, it never panics whatever the size unit
+fuzz_target!(|data: &[u8]| {
+    for size_unit in [SizeUnit::Bytes, SizeUnit::CodePoints, SizeUnit::Utf16] {
+        let options = JsonStatOptions {
+            size_unit,
+            ..JsonStatOptions::default()
+        };
+        let _ = try_extract_stat_from_json_with_options(data, &options);
+    }
+});
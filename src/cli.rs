@@ -0,0 +1,622 @@
+use jsonstat::json_stat_extractor::{LineEnding, SizeUnit};
+
+const DEFAULT_MAX_ARRAY_INDEX: usize = 9;
+const DEFAULT_CDF_POINTS: usize = 20;
+
+pub enum OutputFormat {
+    Json,
+    Dot,
+    Toml,
+    Yaml,
+    OpenApiComponent,
+    ConfluentAvro,
+    BarChart,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+pub struct CliOptions {
+    pub file_name: Option<String>,
+    pub file_names: Vec<String>,
+    pub output_dir: Option<String>,
+    pub heatmap_output: Option<String>,
+    /// File written instead of the standard output, for the binary formats.
+    pub output: Option<String>,
+    pub format: OutputFormat,
+    pub field_stats_only: bool,
+    pub precision: u32,
+    pub keys_only: bool,
+    pub flatten: bool,
+    pub as_array: bool,
+    pub debug: bool,
+    pub min_bytes: Option<usize>,
+    pub document_summary: bool,
+    pub no_aggregate: bool,
+    pub concatenated: bool,
+    pub summarize: bool,
+    pub color: ColorChoice,
+    pub renamed_attributes: Vec<(String, String)>,
+    pub renamed_keys: Vec<(String, String)>,
+    pub skip_values: bool,
+    pub omitted_attributes: Vec<String>,
+    pub warn_mixed_types: bool,
+    pub baseline: Option<String>,
+    pub hash_names: bool,
+    pub jsonc: bool,
+    pub jq_path: Option<String>,
+    pub limit: Option<u64>,
+    pub include_empty: bool,
+    pub flatten_arrays: bool,
+    pub max_array_index: usize,
+    pub size_unit: SizeUnit,
+    pub gzip: bool,
+    pub ndjson: bool,
+    pub per_document: bool,
+    pub normalize: bool,
+    pub include_path_stats: bool,
+    pub collapse_singletons: bool,
+    pub group_by: Option<String>,
+    pub string_size_cap: Option<usize>,
+    pub sample_size: Option<usize>,
+    pub sample_seed: Option<u64>,
+    /// Number of input files picked at random, the directories given being read recursively.
+    pub sample_files: Option<usize>,
+    /// Flattened path of the attribute whose size distribution is given instead of the stat.
+    pub cdf: Option<String>,
+    pub cdf_points: usize,
+    /// Line ending of the pretty size given in the document summary.
+    pub line_ending: Option<LineEnding>,
+}
+
+pub fn parse_cli_options<I>(args: I) -> Result<CliOptions, String>
+where
+    I: Iterator<Item = String>,
+{
+    let mut cli_options = CliOptions {
+        file_name: None,
+        file_names: vec![],
+        output_dir: None,
+        heatmap_output: None,
+        output: None,
+        format: OutputFormat::Json,
+        field_stats_only: false,
+        precision: 4,
+        keys_only: false,
+        flatten: false,
+        as_array: false,
+        debug: false,
+        min_bytes: None,
+        document_summary: false,
+        no_aggregate: false,
+        concatenated: false,
+        summarize: false,
+        color: ColorChoice::Auto,
+        renamed_attributes: vec![],
+        renamed_keys: vec![],
+        skip_values: false,
+        omitted_attributes: vec![],
+        warn_mixed_types: false,
+        baseline: None,
+        hash_names: false,
+        jsonc: false,
+        jq_path: None,
+        limit: None,
+        include_empty: false,
+        flatten_arrays: false,
+        max_array_index: DEFAULT_MAX_ARRAY_INDEX,
+        size_unit: SizeUnit::Bytes,
+        gzip: false,
+        ndjson: false,
+        per_document: false,
+        normalize: false,
+        include_path_stats: false,
+        collapse_singletons: false,
+        group_by: None,
+        string_size_cap: None,
+        sample_size: None,
+        sample_seed: None,
+        sample_files: None,
+        cdf: None,
+        cdf_points: DEFAULT_CDF_POINTS,
+        line_ending: None,
+    };
+    let mut format_given = false;
+    let mut cdf_points_given = false;
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let format = args.next().ok_or("--format expects a value")?;
+                cli_options.format = match format.as_str() {
+                    "json" => OutputFormat::Json,
+                    "dot" => OutputFormat::Dot,
+                    "toml" => OutputFormat::Toml,
+                    "yaml" => OutputFormat::Yaml,
+                    "openapi-component" => OutputFormat::OpenApiComponent,
+                    "confluent-avro" => OutputFormat::ConfluentAvro,
+                    "bar-chart" => OutputFormat::BarChart,
+                    #[cfg(feature = "parquet")]
+                    "parquet" => OutputFormat::Parquet,
+                    #[cfg(not(feature = "parquet"))]
+                    "parquet" => {
+                        return Err("--format parquet requires the parquet feature".to_string())
+                    }
+                    _ => return Err(format!("unknown format {format}")),
+                };
+                format_given = true;
+            }
+            "--field-stats-only" => cli_options.field_stats_only = true,
+            "--keys-only" => cli_options.keys_only = true,
+            "--flatten" => cli_options.flatten = true,
+            "--flatten-arrays" => {
+                cli_options.flatten = true;
+                cli_options.flatten_arrays = true;
+            }
+            "--as-array" => cli_options.as_array = true,
+            "--debug" => cli_options.debug = true,
+            "--document-summary" => cli_options.document_summary = true,
+            "--no-aggregate" => cli_options.no_aggregate = true,
+            "--concatenated" => cli_options.concatenated = true,
+            "--summarize" => cli_options.summarize = true,
+            "--skip-values" => cli_options.skip_values = true,
+            "--warn-mixed-types" => cli_options.warn_mixed_types = true,
+            "--hash-names" => cli_options.hash_names = true,
+            "--jsonc" => cli_options.jsonc = true,
+            "--include-empty" => cli_options.include_empty = true,
+            "--gzip" => cli_options.gzip = true,
+            "--ndjson" => cli_options.ndjson = true,
+            "--per-document" => cli_options.per_document = true,
+            "--normalize" => cli_options.normalize = true,
+            "--include-path-stats" => cli_options.include_path_stats = true,
+            "--collapse-singletons" => cli_options.collapse_singletons = true,
+            "--color" => {
+                let color = args.next().ok_or("--color expects a value")?;
+                cli_options.color = match color.as_str() {
+                    "auto" => ColorChoice::Auto,
+                    "always" => ColorChoice::Always,
+                    "never" => ColorChoice::Never,
+                    _ => return Err(format!("unknown color choice {color}")),
+                };
+            }
+            "--size-unit" => {
+                let size_unit = args.next().ok_or("--size-unit expects a value")?;
+                cli_options.size_unit = match size_unit.as_str() {
+                    "bytes" => SizeUnit::Bytes,
+                    "codepoints" => SizeUnit::CodePoints,
+                    "utf16" => SizeUnit::Utf16,
+                    _ => return Err(format!("unknown size unit {size_unit}")),
+                };
+            }
+            "--rename-attribute" => {
+                let renaming = args.next().ok_or("--rename-attribute expects a value")?;
+                let (old_name, new_name) = renaming
+                    .split_once('=')
+                    .ok_or(format!("invalid attribute renaming {renaming}"))?;
+                cli_options
+                    .renamed_attributes
+                    .push((old_name.to_string(), new_name.to_string()));
+            }
+            "--rename" => {
+                let renaming = args.next().ok_or("--rename expects a value")?;
+                let (old_name, new_name) = renaming
+                    .split_once('=')
+                    .ok_or(format!("invalid key renaming {renaming}"))?;
+                cli_options
+                    .renamed_keys
+                    .push((old_name.to_string(), new_name.to_string()));
+            }
+            "--omit-attribute" => {
+                let name = args.next().ok_or("--omit-attribute expects a value")?;
+                cli_options.omitted_attributes.push(name);
+            }
+            "--baseline" => {
+                let baseline = args.next().ok_or("--baseline expects a file name")?;
+                cli_options.baseline = Some(baseline);
+            }
+            "--group-by" => {
+                let key = args.next().ok_or("--group-by expects a key")?;
+                cli_options.group_by = Some(key);
+            }
+            "--output-dir" => {
+                let output_dir = args.next().ok_or("--output-dir expects a directory")?;
+                cli_options.output_dir = Some(output_dir);
+            }
+            "--heatmap-output" => {
+                let heatmap_output = args.next().ok_or("--heatmap-output expects a file name")?;
+                cli_options.heatmap_output = Some(heatmap_output);
+            }
+            "--output" => {
+                let output = args.next().ok_or("--output expects a file name")?;
+                cli_options.output = Some(output);
+            }
+            "--jq-path" => {
+                let jq_path = args.next().ok_or("--jq-path expects a jq expression")?;
+                cli_options.jq_path = Some(jq_path);
+            }
+            "--min-bytes" => {
+                let min_bytes = args.next().ok_or("--min-bytes expects a value")?;
+                let min_bytes = min_bytes
+                    .parse()
+                    .map_err(|_| format!("invalid min bytes {min_bytes}"))?;
+                cli_options.min_bytes = Some(min_bytes);
+            }
+            "--cap-string" => {
+                let string_size_cap = args.next().ok_or("--cap-string expects a value")?;
+                let string_size_cap = string_size_cap
+                    .parse()
+                    .map_err(|_| format!("invalid string size cap {string_size_cap}"))?;
+                cli_options.string_size_cap = Some(string_size_cap);
+            }
+            "--max-array-index" => {
+                let max_array_index = args.next().ok_or("--max-array-index expects a value")?;
+                cli_options.max_array_index = max_array_index
+                    .parse()
+                    .map_err(|_| format!("invalid max array index {max_array_index}"))?;
+            }
+            "--limit" => {
+                let limit = args.next().ok_or("--limit expects a value")?;
+                let limit = limit
+                    .parse()
+                    .map_err(|_| format!("invalid limit {limit}"))?;
+                cli_options.limit = Some(limit);
+            }
+            "--sample" => {
+                let sample_size = args.next().ok_or("--sample expects a value")?;
+                let sample_size = sample_size
+                    .parse()
+                    .map_err(|_| format!("invalid sample size {sample_size}"))?;
+                cli_options.sample_size = Some(sample_size);
+            }
+            "--sample-seed" => {
+                let sample_seed = args.next().ok_or("--sample-seed expects a value")?;
+                let sample_seed = sample_seed
+                    .parse()
+                    .map_err(|_| format!("invalid sample seed {sample_seed}"))?;
+                cli_options.sample_seed = Some(sample_seed);
+            }
+            "--sample-files" => {
+                let sample_files = args.next().ok_or("--sample-files expects a value")?;
+                let sample_files = sample_files
+                    .parse()
+                    .map_err(|_| format!("invalid sample file count {sample_files}"))?;
+                cli_options.sample_files = Some(sample_files);
+            }
+            "--cdf" => {
+                let cdf = args.next().ok_or("--cdf expects an attribute path")?;
+                cli_options.cdf = Some(cdf);
+            }
+            "--cdf-points" => {
+                let cdf_points = args.next().ok_or("--cdf-points expects a value")?;
+                cli_options.cdf_points = cdf_points
+                    .parse()
+                    .ok()
+                    .filter(|cdf_points| *cdf_points >= 2)
+                    .ok_or(format!("invalid CDF point count {cdf_points}"))?;
+                cdf_points_given = true;
+            }
+            "--line-ending" => {
+                let line_ending = args.next().ok_or("--line-ending expects a value")?;
+                cli_options.line_ending = match line_ending.as_str() {
+                    "lf" => Some(LineEnding::Lf),
+                    "crlf" => Some(LineEnding::Crlf),
+                    _ => return Err(format!("unknown line ending {line_ending}")),
+                };
+            }
+            "--precision" => {
+                let precision = args.next().ok_or("--precision expects a value")?;
+                cli_options.precision = precision
+                    .parse()
+                    .map_err(|_| format!("invalid precision {precision}"))?;
+            }
+            _ => {
+                cli_options.file_names.push(arg.clone());
+                cli_options.file_name = Some(arg);
+            }
+        }
+    }
+    // a compressed NDJSON file is recognized by its extensions, like in events.ndjson.gz
+    if let Some(file_name) = &cli_options.file_name {
+        let uncompressed_file_name = file_name.strip_suffix(".gz");
+        cli_options.gzip |= uncompressed_file_name.is_some();
+        let uncompressed_file_name = uncompressed_file_name.unwrap_or(file_name);
+        cli_options.ndjson |= uncompressed_file_name.ends_with(".ndjson")
+            || uncompressed_file_name.ends_with(".jsonl");
+    }
+    #[cfg(feature = "parquet")]
+    if matches!(cli_options.format, OutputFormat::Parquet) && cli_options.output.is_none() {
+        return Err("--format parquet expects --output".to_string());
+    }
+    if cli_options.summarize && format_given {
+        return Err("--summarize and --format are mutually exclusive".to_string());
+    }
+    if cli_options.output_dir.is_some() && cli_options.file_names.is_empty() {
+        return Err("--output-dir expects input files".to_string());
+    }
+    if cli_options.heatmap_output.is_some() && cli_options.file_names.is_empty() {
+        return Err("--heatmap-output expects input files".to_string());
+    }
+    if cli_options.per_document && !cli_options.ndjson {
+        return Err("--per-document expects NDJSON input".to_string());
+    }
+    if cli_options.sample_files.is_some() && cli_options.file_names.is_empty() {
+        return Err("--sample-files expects input files or directories".to_string());
+    }
+    if cli_options.sample_seed.is_some()
+        && cli_options.sample_size.is_none()
+        && cli_options.sample_files.is_none()
+    {
+        return Err("--sample-seed expects --sample or --sample-files".to_string());
+    }
+    if cdf_points_given && cli_options.cdf.is_none() {
+        return Err("--cdf-points expects --cdf".to_string());
+    }
+    if cli_options.line_ending.is_some() && !cli_options.document_summary {
+        return Err("--line-ending expects --document-summary".to_string());
+    }
+    return Ok(cli_options);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::{parse_cli_options, ColorChoice, OutputFormat};
+    use jsonstat::json_stat_extractor::{LineEnding, SizeUnit};
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        return args.into_iter();
+    }
+
+    #[test]
+    fn it_should_default_to_json_format_on_standard_input() {
+        let cli_options = parse_cli_options(args(&["jsonstat"])).unwrap();
+        assert!(cli_options.file_name.is_none());
+        assert!(matches!(cli_options.format, OutputFormat::Json));
+        assert!(!cli_options.field_stats_only);
+        assert_eq!(cli_options.precision, 4);
+    }
+
+    #[test]
+    fn it_should_parse_format_and_file_name() {
+        let cli_options =
+            parse_cli_options(args(&["jsonstat", "--format", "dot", "myfile.json"])).unwrap();
+        assert_eq!(cli_options.file_name.unwrap(), "myfile.json");
+        assert!(matches!(cli_options.format, OutputFormat::Dot));
+    }
+
+    #[test]
+    fn it_should_parse_limit() {
+        let cli_options = parse_cli_options(args(&["jsonstat", "--limit", "1024"])).unwrap();
+        assert_eq!(cli_options.limit, Some(1024));
+        assert!(parse_cli_options(args(&["jsonstat", "--limit", "-1"])).is_err());
+    }
+
+    #[test]
+    fn it_should_parse_toml_format() {
+        let cli_options = parse_cli_options(args(&["jsonstat", "--format", "toml"])).unwrap();
+        assert!(matches!(cli_options.format, OutputFormat::Toml));
+    }
+
+    #[test]
+    fn it_should_parse_yaml_format() {
+        let cli_options = parse_cli_options(args(&["jsonstat", "--format", "yaml"])).unwrap();
+        assert!(matches!(cli_options.format, OutputFormat::Yaml));
+    }
+
+    #[test]
+    fn it_should_parse_openapi_component_format() {
+        let cli_options =
+            parse_cli_options(args(&["jsonstat", "--format", "openapi-component"])).unwrap();
+        assert!(matches!(cli_options.format, OutputFormat::OpenApiComponent));
+    }
+
+    #[test]
+    fn it_should_parse_bar_chart_format() {
+        let cli_options = parse_cli_options(args(&["jsonstat", "--format", "bar-chart"])).unwrap();
+        assert!(matches!(cli_options.format, OutputFormat::BarChart));
+    }
+
+    #[test]
+    fn it_should_parse_parquet_format_with_an_output_file() {
+        let cli_options = parse_cli_options(args(&[
+            "jsonstat",
+            "--format",
+            "parquet",
+            "--output",
+            "stats.parquet",
+        ]));
+        if cfg!(feature = "parquet") {
+            assert_eq!(
+                cli_options.unwrap().output.as_deref(),
+                Some("stats.parquet")
+            );
+            assert!(parse_cli_options(args(&["jsonstat", "--format", "parquet"])).is_err());
+        } else {
+            assert!(cli_options.is_err());
+        }
+    }
+
+    #[test]
+    fn it_should_parse_confluent_avro_format() {
+        let cli_options =
+            parse_cli_options(args(&["jsonstat", "--format", "confluent-avro"])).unwrap();
+        assert!(matches!(cli_options.format, OutputFormat::ConfluentAvro));
+    }
+
+    #[test]
+    fn it_should_parse_size_unit() {
+        let cli_options = parse_cli_options(args(&["jsonstat"])).unwrap();
+        assert_eq!(cli_options.size_unit, SizeUnit::Bytes);
+        let cli_options = parse_cli_options(args(&["jsonstat", "--size-unit", "utf16"])).unwrap();
+        assert_eq!(cli_options.size_unit, SizeUnit::Utf16);
+        assert!(parse_cli_options(args(&["jsonstat", "--size-unit", "words"])).is_err());
+    }
+
+    #[test]
+    fn it_should_parse_line_ending() {
+        let cli_options = parse_cli_options(args(&["jsonstat", "--document-summary"])).unwrap();
+        assert_eq!(cli_options.line_ending, None);
+        let cli_options = parse_cli_options(args(&[
+            "jsonstat",
+            "--document-summary",
+            "--line-ending",
+            "crlf",
+        ]))
+        .unwrap();
+        assert_eq!(cli_options.line_ending, Some(LineEnding::Crlf));
+        assert!(parse_cli_options(args(&[
+            "jsonstat",
+            "--document-summary",
+            "--line-ending",
+            "cr"
+        ]))
+        .is_err());
+        assert!(parse_cli_options(args(&["jsonstat", "--line-ending", "lf"])).is_err());
+    }
+
+    #[test]
+    fn it_should_detect_gzipped_ndjson_from_the_file_name() {
+        let cli_options = parse_cli_options(args(&["jsonstat", "events.jsonl.gz"])).unwrap();
+        assert!(cli_options.gzip);
+        assert!(cli_options.ndjson);
+        let cli_options = parse_cli_options(args(&["jsonstat", "events.json.gz"])).unwrap();
+        assert!(cli_options.gzip);
+        assert!(!cli_options.ndjson);
+        let cli_options = parse_cli_options(args(&["jsonstat", "--gzip", "--ndjson"])).unwrap();
+        assert!(cli_options.gzip);
+        assert!(cli_options.ndjson);
+    }
+
+    #[test]
+    fn it_should_parse_field_stats_only_flag() {
+        let cli_options =
+            parse_cli_options(args(&["jsonstat", "myfile.json", "--field-stats-only"])).unwrap();
+        assert_eq!(cli_options.file_name.unwrap(), "myfile.json");
+        assert!(cli_options.field_stats_only);
+    }
+
+    #[test]
+    fn it_should_parse_precision() {
+        let cli_options = parse_cli_options(args(&["jsonstat", "--precision", "2"])).unwrap();
+        assert_eq!(cli_options.precision, 2);
+        assert!(parse_cli_options(args(&["jsonstat", "--precision", "-1"])).is_err());
+    }
+
+    #[test]
+    fn it_should_reject_unknown_format() {
+        assert!(parse_cli_options(args(&["jsonstat", "--format", "xml"])).is_err());
+        assert!(parse_cli_options(args(&["jsonstat", "--format"])).is_err());
+    }
+
+    #[test]
+    fn it_should_parse_sample_size_and_seed() {
+        let cli_options = parse_cli_options(args(&[
+            "jsonstat",
+            "--sample",
+            "100",
+            "--sample-seed",
+            "42",
+        ]))
+        .unwrap();
+        assert_eq!(cli_options.sample_size, Some(100));
+        assert_eq!(cli_options.sample_seed, Some(42));
+        assert!(parse_cli_options(args(&["jsonstat", "--sample-seed", "42"])).is_err());
+    }
+
+    #[test]
+    fn it_should_parse_a_cdf_with_its_point_count() {
+        let cli_options = parse_cli_options(args(&[
+            "jsonstat",
+            "--cdf",
+            "users[].name",
+            "--cdf-points",
+            "50",
+        ]))
+        .unwrap();
+        assert_eq!(cli_options.cdf, Some("users[].name".to_string()));
+        assert_eq!(cli_options.cdf_points, 50);
+        assert!(
+            parse_cli_options(args(&["jsonstat", "--cdf", "name", "--cdf-points", "1"])).is_err()
+        );
+        assert!(parse_cli_options(args(&["jsonstat", "--cdf-points", "50"])).is_err());
+        assert!(parse_cli_options(args(&["jsonstat", "--cdf-points", "20"])).is_err());
+    }
+
+    #[test]
+    fn it_should_parse_sample_files_with_a_seed() {
+        let cli_options = parse_cli_options(args(&[
+            "jsonstat",
+            "--sample-files",
+            "10",
+            "--sample-seed",
+            "7",
+            "snapshots",
+        ]))
+        .unwrap();
+        assert_eq!(cli_options.sample_files, Some(10));
+        assert_eq!(cli_options.sample_seed, Some(7));
+        assert!(parse_cli_options(args(&["jsonstat", "--sample-files", "10"])).is_err());
+    }
+
+    #[test]
+    fn it_should_reject_summarize_with_format() {
+        assert!(
+            parse_cli_options(args(&["jsonstat", "--summarize"]))
+                .unwrap()
+                .summarize
+        );
+        assert!(parse_cli_options(args(&["jsonstat", "--summarize", "--format", "json"])).is_err());
+    }
+
+    #[test]
+    fn it_should_parse_color_choice() {
+        let cli_options = parse_cli_options(args(&["jsonstat"])).unwrap();
+        assert!(matches!(cli_options.color, ColorChoice::Auto));
+        let cli_options = parse_cli_options(args(&["jsonstat", "--color", "never"])).unwrap();
+        assert!(matches!(cli_options.color, ColorChoice::Never));
+        assert!(parse_cli_options(args(&["jsonstat", "--color", "blue"])).is_err());
+    }
+
+    #[test]
+    fn it_should_parse_chained_attribute_renamings() {
+        let cli_options = parse_cli_options(args(&[
+            "jsonstat",
+            "--rename-attribute",
+            "a=b",
+            "--rename-attribute",
+            "c=d",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli_options.renamed_attributes,
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("c".to_string(), "d".to_string())
+            ]
+        );
+        assert!(parse_cli_options(args(&["jsonstat", "--rename-attribute", "a"])).is_err());
+    }
+
+    #[test]
+    fn it_should_parse_key_renamings_apart_from_attribute_renamings() {
+        let cli_options = parse_cli_options(args(&[
+            "jsonstat",
+            "--rename",
+            "userId=user_id",
+            "--rename-attribute",
+            "a=b",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli_options.renamed_keys,
+            vec![("userId".to_string(), "user_id".to_string())]
+        );
+        assert_eq!(cli_options.renamed_attributes.len(), 1);
+        assert!(parse_cli_options(args(&["jsonstat", "--rename", "userId"])).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::HashSet;
+
+use serde_json::{json, Value};
+
+use crate::json_stat_extractor::JsonStat::{ArrayStat, ObjStat, ValStat};
+use crate::json_stat_extractor::{merged_value_stat, JsonAttrStat, JsonStat};
+
+const NULL_TYPE: &str = "null";
+
+// the type of the values whose type the stats do not keep, like the items of an array of scalars
+fn any_scalar_schema() -> Value {
+    return json!(["null", "boolean", "double", "string"]);
+}
+
+// an Avro schema named after name: an object is a record, an attribute missing from some objects
+// or having null values is a union with null defaulting to null and an attribute having several
+// types a union of them, the JSON numbers being doubles
+pub fn json_stat_to_avro_schema(stat: &JsonStat, name: &str) -> Value {
+    return avro_schema(stat, &avro_name(name), &mut HashSet::new());
+}
+
+// the request body of the Confluent Schema Registry to register the Avro schema of the stat
+// under subject, the schema being given as a JSON string
+pub fn json_stat_to_schema_registry_avro(stat: &JsonStat, subject: &str) -> Value {
+    let avro_schema = json_stat_to_avro_schema(stat, subject);
+    return json!({"schema": avro_schema.to_string()});
+}
+
+// record_names holds the names of the records already in the schema
+fn avro_schema(stat: &JsonStat, record_name: &str, record_names: &mut HashSet<String>) -> Value {
+    return match stat {
+        ObjStat(obj_stat) => record_schema(&obj_stat.attributes, record_name, record_names),
+        ArrayStat(array_stat) if !array_stat.attributes.is_empty() => json!({
+            "type": "array",
+            "items": record_schema(&array_stat.attributes, record_name, record_names),
+        }),
+        ArrayStat(_) => json!({"type": "array", "items": any_scalar_schema()}),
+        ValStat(_) => any_scalar_schema(),
+    };
+}
+
+// the nested records are named after their path, Avro requiring unique record names and unique
+// field names in a record, a name already taken once sanitized getting a numeric suffix
+fn record_schema(
+    attributes: &[JsonAttrStat],
+    record_name: &str,
+    record_names: &mut HashSet<String>,
+) -> Value {
+    let record_name = unique_name(record_name, record_names);
+    let mut field_names: HashSet<String> = HashSet::new();
+    let fields: Vec<Value> = attributes
+        .iter()
+        .map(|attr_stat| {
+            let field_name = unique_name(&avro_name(&attr_stat.name), &mut field_names);
+            let field_record_name = format!("{record_name}_{field_name}");
+            let mut field = json!({
+                "name": field_name,
+                "type": field_schema(attr_stat, &field_record_name, record_names),
+            });
+            if is_nullable(attr_stat) {
+                field["default"] = Value::Null;
+            }
+            return field;
+        })
+        .collect();
+    return json!({"type": "record", "name": record_name, "fields": fields});
+}
+
+fn is_nullable(attr_stat: &JsonAttrStat) -> bool {
+    return attr_stat.present_ratio < 1.0 || attr_stat.type_counts.contains_key(NULL_TYPE);
+}
+
+// the null branch comes first in a union, the default value of a field having to match it, the
+// branches of a union type like the one of any_scalar_schema are inlined as Avro forbids a union
+// directly inside a union
+fn field_schema(
+    attr_stat: &JsonAttrStat,
+    record_name: &str,
+    record_names: &mut HashSet<String>,
+) -> Value {
+    let mut type_schemas: Vec<Value> = vec![];
+    attr_stat
+        .type_counts
+        .keys()
+        .filter(|type_name| *type_name != NULL_TYPE)
+        .map(|type_name| type_schema(type_name, attr_stat, record_name, record_names))
+        .flat_map(|schema| match schema {
+            Value::Array(branches) => branches,
+            schema => vec![schema],
+        })
+        .for_each(|schema| {
+            if !type_schemas.contains(&schema) {
+                type_schemas.push(schema);
+            }
+        });
+    let null_schema = json!(NULL_TYPE);
+    if is_nullable(attr_stat) || type_schemas.contains(&null_schema) {
+        type_schemas.retain(|schema| *schema != null_schema);
+        type_schemas.insert(0, null_schema);
+    }
+    return match type_schemas.len() {
+        1 => type_schemas.remove(0),
+        _ => Value::Array(type_schemas),
+    };
+}
+
+// the object or array values of an attribute are merged to describe its nested structure
+fn type_schema(
+    type_name: &str,
+    attr_stat: &JsonAttrStat,
+    record_name: &str,
+    record_names: &mut HashSet<String>,
+) -> Value {
+    let nested_stat = || {
+        let filter: fn(&JsonStat) -> bool = match type_name {
+            "object" => |value_stat| matches!(value_stat, ObjStat(_)),
+            _ => |value_stat| matches!(value_stat, ArrayStat(_)),
+        };
+        merged_value_stat(attr_stat, filter)
+    };
+    return match type_name {
+        "string" => json!("string"),
+        "number" => json!("double"),
+        "boolean" => json!("boolean"),
+        "object" | "array" => match nested_stat() {
+            Some(nested_stat) => avro_schema(&nested_stat, record_name, record_names),
+            None => any_scalar_schema(),
+        },
+        _ => any_scalar_schema(),
+    };
+}
+
+// the first free name among name, name_2, name_3 and so on, which is then taken
+fn unique_name(name: &str, taken_names: &mut HashSet<String>) -> String {
+    let unique_name = (1..)
+        .map(|index| match index {
+            1 => name.to_string(),
+            _ => format!("{name}_{index}"),
+        })
+        .find(|candidate| !taken_names.contains(candidate))
+        .unwrap_or_default();
+    taken_names.insert(unique_name.clone());
+    return unique_name;
+}
+
+// an Avro name starts with a letter or an underscore and holds letters, digits and underscores
+fn avro_name(name: &str) -> String {
+    let avro_name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    return match avro_name.chars().next() {
+        Some(c) if !c.is_ascii_digit() => avro_name,
+        _ => format!("_{avro_name}"),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use avro_schema::schema::Schema;
+    use serde_json::{json, Value};
+
+    use crate::exporters::avro::{json_stat_to_avro_schema, json_stat_to_schema_registry_avro};
+    use crate::json_stat_extractor::extract_stat_from_json_value;
+    use crate::json_stat_extractor::JsonStat::ArrayStat;
+
+    // the schema is read by an Avro parser then checked for the rules the parser leaves out:
+    // valid names, unique record names, unique field names in a record and no union in a union
+    fn assert_valid_avro_schema(schema: &Value) {
+        let parsed_schema: Schema = serde_json::from_value(schema.clone()).unwrap();
+        assert_valid_avro_names(&parsed_schema, &mut HashSet::new());
+    }
+
+    fn assert_valid_avro_names(schema: &Schema, record_names: &mut HashSet<String>) {
+        let is_avro_name = |name: &str| {
+            return name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        };
+        match schema {
+            Schema::Record(record) => {
+                assert!(is_avro_name(&record.name), "{}", record.name);
+                assert!(record_names.insert(record.name.clone()), "{}", record.name);
+                let mut field_names = HashSet::new();
+                record.fields.iter().for_each(|field| {
+                    assert!(is_avro_name(&field.name), "{}", field.name);
+                    assert!(field_names.insert(field.name.clone()), "{}", field.name);
+                    assert_valid_avro_names(&field.schema, record_names);
+                });
+            }
+            Schema::Union(branches) => branches.iter().for_each(|branch| {
+                assert!(!matches!(branch, Schema::Union(_)), "{branches:?}");
+                assert_valid_avro_names(branch, record_names);
+            }),
+            Schema::Array(items) | Schema::Map(items) => {
+                assert_valid_avro_names(items, record_names)
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn it_should_give_a_record_with_the_attributes_as_fields() {
+        let stat = extract_stat_from_json_value(json!([
+            {"id": 1, "name": "a", "address": {"city": "Paris"}, "tags": ["x"]},
+            {"id": 2, "name": null, "address": {"city": "Lyon"}}
+        ]));
+        let schema = json_stat_to_avro_schema(&stat, "users-value");
+        assert_eq!(schema["type"], "array");
+        let record = &schema["items"];
+        assert_eq!(record["type"], "record");
+        assert_eq!(record["name"], "users_value");
+        let fields = record["fields"].as_array().unwrap();
+        let field = |name: &str| fields.iter().find(|field| field["name"] == name).unwrap();
+        assert_eq!(field("id"), &json!({"name": "id", "type": "double"}));
+        assert_eq!(
+            field("name"),
+            &json!({"name": "name", "type": ["null", "string"], "default": null})
+        );
+        assert_eq!(field("address")["type"]["name"], "users_value_address");
+        assert_eq!(field("address")["type"]["fields"][0]["type"], "string");
+        assert_eq!(field("tags")["type"][0], "null");
+        assert_eq!(field("tags")["type"][1]["type"], "array");
+        assert_valid_avro_schema(&schema);
+    }
+
+    #[test]
+    fn it_should_give_unique_names_once_sanitized() {
+        let stat = extract_stat_from_json_value(json!({
+            "a-b": 1,
+            "a_b": {"c": 1},
+            "a": {"b": {"c": "x"}},
+            "1st": true
+        }));
+        let schema = json_stat_to_avro_schema(&stat, "doc");
+        assert_valid_avro_schema(&schema);
+        let fields = schema["fields"].as_array().unwrap();
+        let field = |name: &str| fields.iter().find(|field| field["name"] == name).unwrap();
+        assert_eq!(fields.len(), 4);
+        assert_eq!(field("a_b")["type"], "double");
+        assert_eq!(field("a_b_2")["type"]["name"], "doc_a_b_2");
+        assert_eq!(field("a")["type"]["fields"][0]["type"]["name"], "doc_a_b");
+        assert_eq!(field("_1st")["type"], "boolean");
+    }
+
+    #[test]
+    fn it_should_inline_the_scalar_union_in_a_union() {
+        let mut stat = extract_stat_from_json_value(json!([
+            {"meta": {"x": 1}},
+            {"meta": "text"},
+            {"meta": null}
+        ]));
+        if let ArrayStat(array_stat) = &mut stat {
+            array_stat.attributes[0].values.clear();
+        }
+        let schema = json_stat_to_avro_schema(&stat, "doc");
+        assert_valid_avro_schema(&schema);
+        assert_eq!(
+            schema["items"]["fields"][0]["type"],
+            json!(["null", "boolean", "double", "string"])
+        );
+    }
+
+    #[test]
+    fn it_should_give_the_schema_as_a_json_string_for_the_registry() {
+        let stat = extract_stat_from_json_value(json!({"id": 1, "label": "a"}));
+        let registry_body = json_stat_to_schema_registry_avro(&stat, "events-value");
+        let schema: Value =
+            serde_json::from_str(registry_body["schema"].as_str().unwrap()).unwrap();
+        assert_eq!(schema, json_stat_to_avro_schema(&stat, "events-value"));
+        assert_eq!(schema["type"], "record");
+        assert_eq!(schema["fields"].as_array().unwrap().len(), 2);
+        assert_valid_avro_schema(&schema);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::json_stat_extractor::{json_stat_attributes, JsonStat};
+
+const BAR_CHARACTER: &str = "#";
+
+// one line per attribute with a bar of at most width characters, the largest average size taking
+// the full width, the names are padded so that the bars are aligned
+pub fn json_stat_to_ascii_bar_chart(stat: &JsonStat, width: usize) -> String {
+    let attributes = json_stat_attributes(stat);
+    let name_width = bar_chart_name_width(stat);
+    let max_size = attributes
+        .iter()
+        .map(|attr_stat| attr_stat.size)
+        .max()
+        .unwrap_or(0);
+    return attributes
+        .iter()
+        .map(|attr_stat| {
+            let bar_length = (attr_stat.size * width).checked_div(max_size).unwrap_or(0);
+            format!(
+                "  {:<name_width$} | {} {} bytes\n",
+                attr_stat.name,
+                BAR_CHARACTER.repeat(bar_length),
+                attr_stat.size
+            )
+        })
+        .collect();
+}
+
+// the chart fitted in line_width characters, like the width of a terminal, the bars being
+// shortened first and the lines still too long (for a very long name) being clipped
+pub fn json_stat_to_clipped_ascii_bar_chart(
+    stat: &JsonStat,
+    width: usize,
+    line_width: usize,
+) -> String {
+    let name_width = bar_chart_name_width(stat);
+    let text_width = json_stat_attributes(stat)
+        .iter()
+        .map(|attr_stat| format!("  {:<name_width$} |  {} bytes", "", attr_stat.size).len())
+        .max()
+        .unwrap_or(0);
+    let bar_width = width.min(line_width.saturating_sub(text_width));
+    return json_stat_to_ascii_bar_chart(stat, bar_width)
+        .lines()
+        .map(|line| line.chars().take(line_width).collect::<String>() + "\n")
+        .collect();
+}
+
+fn bar_chart_name_width(stat: &JsonStat) -> usize {
+    return json_stat_attributes(stat)
+        .iter()
+        .map(|attr_stat| attr_stat.name.chars().count())
+        .max()
+        .unwrap_or(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::exporters::bar_chart::{
+        json_stat_to_ascii_bar_chart, json_stat_to_clipped_ascii_bar_chart,
+    };
+    use crate::json_stat_extractor::extract_stat_from_json_value;
+
+    #[test]
+    fn it_should_scale_bars_to_the_largest_attribute() {
+        let json_stat = extract_stat_from_json_value(json!({
+            "description": "01234567",
+            "id": 1234,
+            "ok": true
+        }));
+        let chart = json_stat_to_ascii_bar_chart(&json_stat, 10);
+        assert_eq!(
+            chart,
+            "  description | ########## 10 bytes\n  id          | #### 4 bytes\n  ok          | #### 4 bytes\n"
+        );
+    }
+
+    #[test]
+    fn it_should_draw_no_bar_without_attributes() {
+        let json_stat = extract_stat_from_json_value(json!("value"));
+        assert_eq!(json_stat_to_ascii_bar_chart(&json_stat, 10), "");
+    }
+
+    #[test]
+    fn it_should_fit_the_bars_in_the_line_width() {
+        let json_stat = extract_stat_from_json_value(json!({
+            "description": "01234567",
+            "id": 1234
+        }));
+        let chart = json_stat_to_clipped_ascii_bar_chart(&json_stat, 10, 30);
+        assert_eq!(
+            chart,
+            "  description | ##### 10 bytes\n  id          | ## 4 bytes\n"
+        );
+        assert!(chart.lines().all(|line| line.chars().count() <= 30));
+        assert_eq!(
+            json_stat_to_clipped_ascii_bar_chart(&json_stat, 10, 80),
+            json_stat_to_ascii_bar_chart(&json_stat, 10)
+        );
+        let chart = json_stat_to_clipped_ascii_bar_chart(&json_stat, 10, 12);
+        assert_eq!(chart, "  descriptio\n  id        \n");
+    }
+}
@@ -0,0 +1,296 @@
+use itertools::Itertools;
+
+use crate::json_stat_error::JsonStatError;
+use crate::json_stat_extractor::JsonStat::ObjStat;
+use crate::json_stat_extractor::{
+    json_stat_attributes, size_percentage, JsonAttrStat, JsonObjStat, JsonStat,
+};
+
+const HEATMAP_LABEL_HEADER: &str = "label";
+const CURLY_BRACKETS_SIZE: usize = 2;
+
+const CSV_HEADER: [&str; 8] = [
+    "name",
+    "size",
+    "count",
+    "total_size",
+    "min_size",
+    "max_size",
+    "size_pct",
+    "present_ratio",
+];
+
+// a header row then one row per attribute of the stat, the cells are left unquoted so that the
+// caller picks its own CSV writer
+pub fn json_stat_to_csv_rows(json_stat: &JsonStat) -> Vec<Vec<String>> {
+    let header_row: Vec<String> = CSV_HEADER.iter().map(|cell| cell.to_string()).collect();
+    let attribute_rows = json_stat_attributes(json_stat).iter().map(|attr_stat| {
+        vec![
+            attr_stat.name.clone(),
+            attr_stat.size.to_string(),
+            attr_stat.count.to_string(),
+            attr_stat.total_size.to_string(),
+            attr_stat.min_size.to_string(),
+            attr_stat.max_size.to_string(),
+            attr_stat.size_pct.to_string(),
+            attr_stat.present_ratio.to_string(),
+        ]
+    });
+    return std::iter::once(header_row).chain(attribute_rows).collect();
+}
+
+// one row per labeled stat and one column per top level attribute of any of the stats, in order
+// of first appearance, the cells being the average sizes of the attributes, empty for a stat
+// without the attribute
+pub fn json_stat_to_heatmap_csv(stats: &[(String, JsonStat)]) -> String {
+    let attribute_names: Vec<&str> = stats
+        .iter()
+        .flat_map(|(_, json_stat)| json_stat_attributes(json_stat))
+        .map(|attr_stat| attr_stat.name.as_str())
+        .unique()
+        .collect();
+    let header_row = std::iter::once(HEATMAP_LABEL_HEADER)
+        .chain(attribute_names.iter().copied())
+        .map(csv_cell)
+        .join(",");
+    let stat_rows = stats.iter().map(|(label, json_stat)| {
+        let attributes = json_stat_attributes(json_stat);
+        let size_cells = attribute_names.iter().map(|attribute_name| {
+            return attributes
+                .iter()
+                .find(|attr_stat| &attr_stat.name == attribute_name)
+                .map(|attr_stat| attr_stat.size.to_string())
+                .unwrap_or_default();
+        });
+        return std::iter::once(csv_cell(label)).chain(size_cells).join(",");
+    });
+    return std::iter::once(header_row)
+        .chain(stat_rows)
+        .map(|row| row + "\n")
+        .collect();
+}
+
+// a cell holding a comma, a double quote or a line break is quoted, its double quotes doubled
+fn csv_cell(cell: &str) -> String {
+    return if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    };
+}
+
+// an object stat with one attribute per row, like the rows of json_stat_to_csv_rows, the first
+// row being skipped as a header when its count is not a number, the minimum and maximum sizes of
+// an attribute being its average size as they are not read, the object count being the highest
+// attribute count as every object is taken to hold the most frequent attribute, a zero count
+// being rejected as an attribute is found at least once
+pub fn json_stat_from_csv(
+    csv: &str,
+    name_col: usize,
+    avg_size_col: usize,
+    count_col: usize,
+) -> Result<JsonStat, JsonStatError> {
+    let rows = parse_csv_rows(csv)?;
+    let has_header = rows
+        .first()
+        .and_then(|row| row.get(count_col))
+        .is_some_and(|count_cell| count_cell.trim().parse::<usize>().is_err());
+    let mut attributes = rows
+        .iter()
+        .enumerate()
+        .skip(usize::from(has_header))
+        .map(|(row_index, row)| {
+            let cell = |col: usize| {
+                row.get(col).ok_or_else(|| {
+                    JsonStatError::InvalidCsv(format!("row {} has no column {col}", row_index + 1))
+                })
+            };
+            let figure = |col: usize| {
+                let figure_cell = cell(col)?;
+                figure_cell.trim().parse::<usize>().map_err(|_| {
+                    JsonStatError::InvalidCsv(format!(
+                        "row {}: {figure_cell} is not a size",
+                        row_index + 1
+                    ))
+                })
+            };
+            let size = figure(avg_size_col)?;
+            let count = figure(count_col)?;
+            if count == 0 {
+                return Err(JsonStatError::InvalidCsv(format!(
+                    "row {}: the count is 0",
+                    row_index + 1
+                )));
+            }
+            return Ok(JsonAttrStat::new(cell(name_col)?, size, count, size, size));
+        })
+        .collect::<Result<Vec<JsonAttrStat>, JsonStatError>>()?;
+    let count = attributes
+        .iter()
+        .map(|attr_stat| attr_stat.count)
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    let attribute_occurrences: usize = attributes.iter().map(|attr_stat| attr_stat.count).sum();
+    let size_of_commas = attribute_occurrences.saturating_sub(count);
+    let attr_total_size = |attr_stat: &JsonAttrStat| {
+        return attr_stat.total_size + attr_stat.key_size * attr_stat.count;
+    };
+    let total_size = CURLY_BRACKETS_SIZE * count
+        + size_of_commas
+        + attributes.iter().map(attr_total_size).sum::<usize>();
+    attributes.iter_mut().for_each(|attr_stat| {
+        attr_stat.size_pct = size_percentage(attr_total_size(attr_stat), total_size);
+        attr_stat.present_ratio = attr_stat.count as f64 / count as f64;
+    });
+    let size = total_size / count;
+    return Ok(ObjStat(JsonObjStat::new(
+        size, count, size, size, attributes,
+    )));
+}
+
+// the quoted cells may hold commas, line breaks and doubled double quotes, the empty lines are
+// skipped
+fn parse_csv_rows(csv: &str) -> Result<Vec<Vec<String>>, JsonStatError> {
+    let mut rows: Vec<Vec<String>> = vec![];
+    let mut row: Vec<String> = vec![];
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if cell.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut cell)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut cell));
+                if row.iter().any(|cell| !cell.is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            _ => cell.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(JsonStatError::InvalidCsv("unterminated quote".to_string()));
+    }
+    row.push(cell);
+    if row.iter().any(|cell| !cell.is_empty()) {
+        rows.push(row);
+    }
+    return Ok(rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use itertools::Itertools;
+
+    use crate::exporters::csv::{
+        csv_cell, json_stat_from_csv, json_stat_to_csv_rows, json_stat_to_heatmap_csv,
+    };
+    use crate::json_stat_error::JsonStatError;
+    use crate::json_stat_extractor::JsonStat::ObjStat;
+    use crate::json_stat_extractor::{extract_stat_from_json_value, merge_stats};
+    use crate::json_stat_transformer::normalize_stat;
+
+    #[test]
+    fn it_should_give_a_header_and_one_row_per_attribute() {
+        let json_stat = extract_stat_from_json_value(json!([{"a": 1, "b": "x"}, {"a": 22}]));
+        let rows = json_stat_to_csv_rows(&json_stat);
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|row| row.len() == rows[0].len()));
+        assert_eq!(rows[0][0], "name");
+        let a_row = rows.iter().find(|row| row[0] == "a").unwrap();
+        assert_eq!(a_row[1..4], ["1", "2", "3"]);
+    }
+
+    #[test]
+    fn it_should_give_only_the_header_for_a_scalar() {
+        let json_stat = extract_stat_from_json_value(json!(1));
+        assert_eq!(json_stat_to_csv_rows(&json_stat).len(), 1);
+    }
+
+    #[test]
+    fn it_should_give_one_row_per_stat_and_one_column_per_attribute() {
+        let stats = vec![
+            (
+                "10:00".to_string(),
+                extract_stat_from_json_value(json!({"id": 1, "name": "abc"})),
+            ),
+            (
+                "11:00".to_string(),
+                extract_stat_from_json_value(json!([{"id": 12, "tags": ["x"]}, {"id": 3}])),
+            ),
+            (
+                "12:00, late".to_string(),
+                extract_stat_from_json_value(json!(1)),
+            ),
+        ];
+        let heatmap_csv = json_stat_to_heatmap_csv(&stats);
+        let rows: Vec<&str> = heatmap_csv.lines().collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], "label,id,name,tags");
+        assert_eq!(rows[1], "10:00,1,5,");
+        assert_eq!(rows[2], "11:00,1,,5");
+        assert_eq!(rows[3], "\"12:00, late\",,,");
+    }
+
+    #[test]
+    fn it_should_import_the_exported_attribute_sizes() {
+        let mut json_stat = merge_stats(
+            extract_stat_from_json_value(json!({"id": 1, "name, first": "abc", "ok": true})),
+            extract_stat_from_json_value(json!({"name, first": "def", "id": 2})),
+        );
+        let csv: String = json_stat_to_csv_rows(&json_stat)
+            .iter()
+            .map(|row| row.iter().map(|cell| csv_cell(cell)).join(",") + "\n")
+            .collect();
+        let mut imported_stat = json_stat_from_csv(&csv, 0, 1, 2).unwrap();
+        normalize_stat(&mut json_stat);
+        normalize_stat(&mut imported_stat);
+        assert_eq!(
+            json_stat_to_csv_rows(&imported_stat),
+            json_stat_to_csv_rows(&json_stat)
+        );
+        assert!(matches!(
+            &imported_stat,
+            ObjStat(obj_stat) if obj_stat.count == 2 && obj_stat.size == 66 / 2
+        ));
+        let merged_stat = merge_stats(
+            imported_stat,
+            extract_stat_from_json_value(json!({"id": 333})),
+        );
+        let merged_rows = json_stat_to_csv_rows(&merged_stat);
+        let id_row = merged_rows.iter().find(|row| row[0] == "id").unwrap();
+        assert_eq!(id_row[2], "3");
+    }
+
+    #[test]
+    fn it_should_read_quoted_names_and_reject_invalid_sizes() {
+        let json_stat = json_stat_from_csv("\"a, \"\"b\"\"\",12,3\n", 0, 1, 2).unwrap();
+        let rows = json_stat_to_csv_rows(&json_stat);
+        assert_eq!(rows[1][..3], ["a, \"b\"", "12", "3"]);
+        assert_eq!(
+            json_stat_from_csv("name,size,count\nid,x,1\n", 0, 1, 2),
+            Err(JsonStatError::InvalidCsv(
+                "row 2: x is not a size".to_string()
+            ))
+        );
+        assert!(json_stat_from_csv("id,1\n", 0, 1, 2).is_err());
+        assert_eq!(
+            json_stat_from_csv("id,5,0\n", 0, 1, 2),
+            Err(JsonStatError::InvalidCsv(
+                "row 1: the count is 0".to_string()
+            ))
+        );
+        assert!(json_stat_from_csv("\"id,1,1\n", 0, 1, 2).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use crate::json_stat_extractor::JsonStat::{ArrayStat, ObjStat, ValStat};
+use crate::json_stat_extractor::{json_stat_size, JsonAttrStat, JsonStat};
+
+const ROOT_NODE_ID: &str = "root";
+
+struct DotNode {
+    label: String,
+    parent_id: Option<String>,
+}
+
+pub fn json_stat_to_dot(json_stat: &JsonStat) -> String {
+    let mut nodes: BTreeMap<String, DotNode> = BTreeMap::new();
+    nodes.insert(
+        ROOT_NODE_ID.to_string(),
+        DotNode {
+            label: node_label("root", json_stat_kind(json_stat), json_stat_size(json_stat)),
+            parent_id: None,
+        },
+    );
+    collect_dot_nodes(json_stat, ROOT_NODE_ID, "", &mut nodes);
+    let node_lines = nodes
+        .iter()
+        .map(|(node_id, node)| format!("  \"{}\" [label=\"{}\"];\n", escape(node_id), node.label));
+    let edge_lines = nodes.iter().filter_map(|(node_id, node)| {
+        node.parent_id
+            .as_ref()
+            .map(|parent_id| format!("  \"{}\" -> \"{}\";\n", escape(parent_id), escape(node_id)))
+    });
+    let body: String = node_lines.chain(edge_lines).collect();
+    return format!("digraph jsonstat {{\n{body}}}\n");
+}
+
+// node ids are the JSON pointer paths of the attributes so that they stay stable between runs;
+// when several values share a path (array items) the first one labels the node
+fn collect_dot_nodes(
+    json_stat: &JsonStat,
+    parent_id: &str,
+    parent_path: &str,
+    nodes: &mut BTreeMap<String, DotNode>,
+) {
+    let attributes: &Vec<JsonAttrStat> = match json_stat {
+        ValStat(_) => return,
+        ObjStat(vs) => &vs.attributes,
+        ArrayStat(vs) => &vs.attributes,
+    };
+    attributes.iter().for_each(|attr_stat| {
+        let escaped_name = attr_stat.name.replace('~', "~0").replace('/', "~1");
+        let attr_path = format!("{parent_path}/{escaped_name}");
+        let kind = attr_stat.values.first().map_or("Value", json_stat_kind);
+        nodes.entry(attr_path.clone()).or_insert(DotNode {
+            label: node_label(&attr_stat.name, kind, attr_stat.size),
+            parent_id: Some(parent_id.to_string()),
+        });
+        attr_stat
+            .values
+            .iter()
+            .for_each(|value_stat| collect_dot_nodes(value_stat, &attr_path, &attr_path, nodes));
+    });
+}
+
+fn json_stat_kind(json_stat: &JsonStat) -> &'static str {
+    return match json_stat {
+        ValStat(_) => "Value",
+        ObjStat(_) => "Object",
+        ArrayStat(_) => "Array",
+    };
+}
+
+fn node_label(name: &str, kind: &str, size: usize) -> String {
+    return format!("{}\\n{kind} {size} bytes", escape(name));
+}
+
+fn escape(text: &str) -> String {
+    return text.replace('\\', "\\\\").replace('"', "\\\"");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Error;
+    use std::result::IntoIter;
+
+    use serde_json::{json, Value};
+
+    use crate::exporters::dot::json_stat_to_dot;
+    use crate::json_stat_extractor::extract_stat_from_json_iter;
+
+    #[test]
+    fn it_should_wrap_nodes_in_a_digraph() {
+        let result_value: Result<Value, Error> = Ok(json!({"test":"test"}));
+        let json_iter: IntoIter<Value> = result_value.into_iter();
+        let result = extract_stat_from_json_iter(json_iter);
+        let dot = json_stat_to_dot(&result);
+        assert!(dot.starts_with("digraph jsonstat {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("  \"root\" [label=\"root\\nObject 15 bytes\"];\n"));
+        assert!(dot.contains("  \"/test\" [label=\"test\\nValue 6 bytes\"];\n"));
+        assert!(dot.contains("  \"root\" -> \"/test\";\n"));
+    }
+
+    #[test]
+    fn it_should_provide_one_node_per_attribute_path() {
+        let result_value: Result<Value, Error> =
+            Ok(json!([{"a":{"b": "0123456789"}}, {"a":{"b": "0123456789"}}]));
+        let json_iter: IntoIter<Value> = result_value.into_iter();
+        let result = extract_stat_from_json_iter(json_iter);
+        let dot = json_stat_to_dot(&result);
+        assert!(dot.contains("  \"root\" [label=\"root\\nArray 51 bytes\"];\n"));
+        assert!(dot.contains("  \"/a\" [label=\"a\\nObject 18 bytes\"];\n"));
+        assert!(dot.contains("  \"/a/b\" [label=\"b\\nValue 12 bytes\"];\n"));
+        assert!(dot.contains("  \"/a\" -> \"/a/b\";\n"));
+        assert_eq!(dot.matches("\"/a/b\" [label").count(), 1);
+    }
+}
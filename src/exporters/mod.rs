@@ -0,0 +1,12 @@
+pub mod avro;
+pub mod bar_chart;
+pub mod csv;
+pub mod dot;
+pub mod openapi;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod sparkline;
+pub mod sql;
+pub mod summary;
+pub mod toml;
+pub mod yaml;
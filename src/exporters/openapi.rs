@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::json_stat_extractor::JsonStat::{ArrayStat, ObjStat, ValStat};
+use crate::json_stat_extractor::{merged_nested_stat, JsonArrayStat, JsonAttrStat, JsonStat};
+
+const NULL_TYPE: &str = "null";
+const OBJECT_TYPE: &str = "object";
+const ARRAY_TYPE: &str = "array";
+// OpenAPI 3.0 has no null type and nullable needs a type, the values being always null
+const NULL_ONLY_TYPE: &str = "string";
+
+// an OpenAPI 3.0 schema to embed under components/schemas/<component_name>, titled by the
+// component name: the attributes present in every object are required, a property or an array
+// item having null values is nullable and one having several other types is a oneOf, OpenAPI 3.0
+// having no type list
+pub fn json_stat_to_openapi_schema_component(stat: &JsonStat, component_name: &str) -> Value {
+    let mut schema = openapi_schema(stat);
+    schema["title"] = json!(component_name);
+    return schema;
+}
+
+fn openapi_schema(stat: &JsonStat) -> Value {
+    return match stat {
+        ObjStat(obj_stat) => object_schema(&obj_stat.attributes),
+        ArrayStat(array_stat) => array_schema(array_stat),
+        ValStat(_) => json!({}),
+    };
+}
+
+// the items of NDJSON documents have no type counts, their attributes giving an object schema
+fn array_schema(array_stat: &JsonArrayStat) -> Value {
+    let items_schema = if array_stat.item_type_counts.is_empty() {
+        if array_stat.attributes.is_empty() {
+            json!({})
+        } else {
+            object_schema(&array_stat.attributes)
+        }
+    } else {
+        typed_schema(&array_stat.item_type_counts, |type_name| {
+            match (type_name, &array_stat.nested_array_stats) {
+                (OBJECT_TYPE, _) => object_schema(&array_stat.attributes),
+                (_, Some(nested_array_stat)) => array_schema(nested_array_stat),
+                (_, None) => json!({"type": ARRAY_TYPE, "items": {}}),
+            }
+        })
+    };
+    return json!({"type": ARRAY_TYPE, "items": items_schema});
+}
+
+// one schema per JSON type, the object and array types being described by collection_schema,
+// several types giving a oneOf
+fn typed_schema<F>(type_counts: &BTreeMap<String, usize>, collection_schema: F) -> Value
+where
+    F: Fn(&str) -> Value,
+{
+    let mut type_schemas: Vec<Value> = type_counts
+        .keys()
+        .filter(|type_name| *type_name != NULL_TYPE)
+        .map(|type_name| match type_name.as_str() {
+            OBJECT_TYPE | ARRAY_TYPE => collection_schema(type_name),
+            type_name => json!({"type": type_name}),
+        })
+        .collect();
+    let is_nullable = type_counts.contains_key(NULL_TYPE);
+    let mut schema = match type_schemas.len() {
+        0 if is_nullable => json!({"type": NULL_ONLY_TYPE}),
+        0 => json!({}),
+        1 => type_schemas.remove(0),
+        _ => json!({"oneOf": type_schemas}),
+    };
+    if is_nullable {
+        schema["nullable"] = json!(true);
+    }
+    return schema;
+}
+
+fn object_schema(attributes: &[JsonAttrStat]) -> Value {
+    let properties: Map<String, Value> = attributes
+        .iter()
+        .map(|attr_stat| (attr_stat.name.clone(), property_schema(attr_stat)))
+        .collect();
+    let required: Vec<&str> = attributes
+        .iter()
+        .filter(|attr_stat| attr_stat.present_ratio >= 1.0)
+        .map(|attr_stat| attr_stat.name.as_str())
+        .collect();
+    let mut schema = json!({"type": "object", "properties": properties});
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    return schema;
+}
+
+// the object and array values of a property are merged to describe its nested structure
+fn property_schema(attr_stat: &JsonAttrStat) -> Value {
+    let nested_stat = merged_nested_stat(attr_stat);
+    return typed_schema(&attr_stat.type_counts, |type_name| {
+        match (type_name, &nested_stat) {
+            (OBJECT_TYPE, Some(ObjStat(obj_stat))) => object_schema(&obj_stat.attributes),
+            (ARRAY_TYPE, Some(ArrayStat(array_stat))) => array_schema(array_stat),
+            (ARRAY_TYPE, _) => json!({"type": ARRAY_TYPE, "items": {}}),
+            _ => json!({"type": OBJECT_TYPE, "properties": {}}),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map, Value};
+
+    use crate::exporters::openapi::json_stat_to_openapi_schema_component;
+    use crate::json_stat_extractor::extract_stat_from_json_value;
+
+    #[test]
+    fn it_should_give_an_object_schema_with_the_attributes_as_properties() {
+        let stat = extract_stat_from_json_value(json!({
+            "id": 1,
+            "name": null,
+            "address": {"city": "Paris"},
+            "tags": ["a"]
+        }));
+        let component = json_stat_to_openapi_schema_component(&stat, "User");
+        let schema: Map<String, Value> = serde_json::from_value(component).unwrap();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["title"], "User");
+        let mut required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(Value::as_str)
+            .collect();
+        required.sort_unstable();
+        assert_eq!(required, vec!["address", "id", "name", "tags"]);
+        let properties = &schema["properties"];
+        assert_eq!(properties["id"], json!({"type": "number"}));
+        assert_eq!(
+            properties["name"],
+            json!({"type": "string", "nullable": true})
+        );
+        assert_eq!(properties["address"]["type"], "object");
+        assert_eq!(
+            properties["address"]["properties"]["city"]["type"],
+            "string"
+        );
+        assert_eq!(
+            properties["tags"],
+            json!({"type": "array", "items": {"type": "string"}})
+        );
+    }
+
+    #[test]
+    fn it_should_give_the_type_of_the_array_items() {
+        let stat = extract_stat_from_json_value(json!({
+            "scores": [1, 2],
+            "mixed": ["a", 1, null],
+            "empty": [],
+            "matrix": [[1], [2, 3]],
+            "users": [{"id": 1}, null]
+        }));
+        let properties = &json_stat_to_openapi_schema_component(&stat, "Doc")["properties"];
+        assert_eq!(properties["scores"]["items"], json!({"type": "number"}));
+        assert_eq!(
+            properties["mixed"]["items"],
+            json!({"oneOf": [{"type": "number"}, {"type": "string"}], "nullable": true})
+        );
+        assert_eq!(properties["empty"]["items"], json!({}));
+        assert_eq!(
+            properties["matrix"]["items"],
+            json!({"type": "array", "items": {"type": "number"}})
+        );
+        assert_eq!(properties["users"]["items"]["type"], "object");
+        assert_eq!(properties["users"]["items"]["nullable"], true);
+        assert_eq!(
+            properties["users"]["items"]["properties"]["id"],
+            json!({"type": "number"})
+        );
+    }
+
+    #[test]
+    fn it_should_require_only_the_attributes_of_every_item() {
+        let stat = extract_stat_from_json_value(json!([
+            {"id": 1, "note": "x"},
+            {"id": 2, "note": 3},
+            {"id": 3}
+        ]));
+        let component = json_stat_to_openapi_schema_component(&stat, "Items");
+        assert_eq!(component["type"], "array");
+        assert_eq!(component["items"]["required"], json!(["id"]));
+        assert_eq!(
+            component["items"]["properties"]["note"],
+            json!({"oneOf": [{"type": "number"}, {"type": "string"}]})
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use itertools::Itertools;
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+
+use crate::json_stat_extractor::JsonStat;
+use crate::json_stat_flattener::{flatten_json_stat, JsonFlatAttrStat};
+
+const TYPE_SEPARATOR: &str = "|";
+
+// one row per flattened attribute path, like in --flatten, the type column holding the JSON types
+// of the values joined by |, like number|string
+pub fn json_stat_to_parquet<W>(json_stat: &JsonStat, writer: W) -> Result<(), ParquetError>
+where
+    W: Write + Send,
+{
+    let flat_attr_stats = flatten_json_stat(json_stat);
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("path", DataType::Utf8, false),
+        Field::new("count", DataType::UInt64, false),
+        Field::new("size", DataType::UInt64, false),
+        Field::new("min_size", DataType::UInt64, false),
+        Field::new("max_size", DataType::UInt64, false),
+        Field::new("type", DataType::Utf8, false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            flat_attr_stats
+                .iter()
+                .map(|flat_attr_stat| &flat_attr_stat.path),
+        )),
+        figure_column(&flat_attr_stats, |flat_attr_stat| flat_attr_stat.count),
+        figure_column(&flat_attr_stats, |flat_attr_stat| flat_attr_stat.size),
+        figure_column(&flat_attr_stats, |flat_attr_stat| flat_attr_stat.min_size),
+        figure_column(&flat_attr_stats, |flat_attr_stat| flat_attr_stat.max_size),
+        Arc::new(StringArray::from_iter_values(flat_attr_stats.iter().map(
+            |flat_attr_stat| flat_attr_stat.type_names.iter().join(TYPE_SEPARATOR),
+        ))),
+    ];
+    let record_batch = RecordBatch::try_new(schema.clone(), columns)?;
+    let mut arrow_writer = ArrowWriter::try_new(writer, schema, None)?;
+    arrow_writer.write(&record_batch)?;
+    arrow_writer.close()?;
+    return Ok(());
+}
+
+fn figure_column(
+    flat_attr_stats: &[JsonFlatAttrStat],
+    figure: fn(&JsonFlatAttrStat) -> usize,
+) -> ArrayRef {
+    return Arc::new(UInt64Array::from_iter_values(
+        flat_attr_stats
+            .iter()
+            .map(|flat_attr_stat| figure(flat_attr_stat) as u64),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use arrow_array::{Array, StringArray, UInt64Array};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use serde_json::json;
+
+    use crate::exporters::parquet::json_stat_to_parquet;
+    use crate::json_stat_extractor::extract_stat_from_json_value;
+
+    #[test]
+    fn it_should_write_one_row_per_attribute_path() {
+        let json_stat = extract_stat_from_json_value(json!({
+            "id": 1,
+            "users": [{"name": "abc"}, {"name": 12}]
+        }));
+        let parquet_path =
+            std::env::temp_dir().join(format!("jsonstat-{}.parquet", std::process::id()));
+        json_stat_to_parquet(&json_stat, File::create(&parquet_path).unwrap()).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&parquet_path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let record_batches: Vec<_> = reader.map(Result::unwrap).collect();
+        std::fs::remove_file(parquet_path).unwrap();
+        assert_eq!(record_batches.len(), 1);
+        let record_batch = &record_batches[0];
+        assert_eq!(record_batch.num_rows(), 3);
+        let column = |name: &str| record_batch.column_by_name(name).unwrap().clone();
+        let paths = column("path");
+        let paths = paths.as_any().downcast_ref::<StringArray>().unwrap();
+        let name_row = (0..paths.len())
+            .find(|row| paths.value(*row) == "users[].name")
+            .unwrap();
+        let counts = column("count");
+        let counts = counts.as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(counts.value(name_row), 2);
+        let max_sizes = column("max_size");
+        let max_sizes = max_sizes.as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(max_sizes.value(name_row), 5);
+        let types = column("type");
+        let types = types.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(types.value(name_row), "number|string");
+    }
+}
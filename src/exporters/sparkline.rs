@@ -0,0 +1,45 @@
+const SPARKLINE_BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const BUCKET_COUNT: usize = 8;
+
+// the range of the sizes is divided into 8 buckets, each bucket is drawn as a block as high as its
+// share of the sizes relative to the fullest bucket, the sizes being stored in `JsonArrayStat`
+// with the `item_sizes` feature
+pub fn json_stat_to_sparkline(sizes: &[usize]) -> String {
+    let (Some(min_size), Some(max_size)) = (sizes.iter().min(), sizes.iter().max()) else {
+        return String::new();
+    };
+    let size_range = max_size - min_size + 1;
+    let mut bucket_counts = [0_usize; BUCKET_COUNT];
+    sizes.iter().for_each(|size| {
+        bucket_counts[(size - min_size) * BUCKET_COUNT / size_range] += 1;
+    });
+    let max_bucket_count = bucket_counts.iter().max().copied().unwrap_or(0);
+    return bucket_counts
+        .iter()
+        .map(|bucket_count| {
+            SPARKLINE_BLOCKS[bucket_count * (SPARKLINE_BLOCKS.len() - 1) / max_bucket_count]
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exporters::sparkline::json_stat_to_sparkline;
+
+    #[test]
+    fn it_should_draw_identical_blocks_for_uniform_sizes() {
+        let sizes: Vec<usize> = (10..26).collect();
+        assert_eq!(json_stat_to_sparkline(&sizes), "████████");
+    }
+
+    #[test]
+    fn it_should_draw_ascending_blocks_for_increasing_sizes() {
+        let sizes: Vec<usize> = (0..8).flat_map(|bucket| vec![bucket; bucket + 1]).collect();
+        assert_eq!(json_stat_to_sparkline(&sizes), "▁▂▃▄▅▆▇█");
+    }
+
+    #[test]
+    fn it_should_draw_nothing_without_sizes() {
+        assert_eq!(json_stat_to_sparkline(&[]), "");
+    }
+}
@@ -0,0 +1,108 @@
+use serde_json::{Map, Value};
+
+use crate::json_stat_extractor::{json_stat_attributes, JsonStat};
+
+const ROWS_PER_INSERT: usize = 1000;
+
+// one INSERT statement per 1000 rows, the columns being the attributes of the stat in their order,
+// a missing or null value gives NULL, an object or an array is inserted as its JSON text, nothing
+// is inserted without values or attributes
+pub fn json_stat_to_sql_insert(
+    stat: &JsonStat,
+    table_name: &str,
+    values: &[Map<String, Value>],
+) -> String {
+    let column_names: Vec<&str> = json_stat_attributes(stat)
+        .iter()
+        .map(|attr_stat| attr_stat.name.as_str())
+        .collect();
+    if column_names.is_empty() {
+        return String::new();
+    }
+    let columns = column_names
+        .iter()
+        .map(|column_name| sql_identifier(column_name))
+        .collect::<Vec<String>>()
+        .join(", ");
+    return values
+        .chunks(ROWS_PER_INSERT)
+        .map(|rows| {
+            let rows = rows
+                .iter()
+                .map(|row| {
+                    let row_values: Vec<String> = column_names
+                        .iter()
+                        .map(|column_name| sql_value(row.get(*column_name)))
+                        .collect();
+                    format!("({})", row_values.join(", "))
+                })
+                .collect::<Vec<String>>()
+                .join(",\n");
+            format!(
+                "INSERT INTO {} ({columns}) VALUES\n{rows};\n",
+                sql_identifier(table_name)
+            )
+        })
+        .collect();
+}
+
+fn sql_identifier(name: &str) -> String {
+    return format!("\"{}\"", name.replace('"', "\"\""));
+}
+
+fn sql_string(text: &str) -> String {
+    return format!("'{}'", text.replace('\'', "''"));
+}
+
+fn sql_value(json_value: Option<&Value>) -> String {
+    return match json_value {
+        None | Some(Value::Null) => "NULL".to_string(),
+        Some(Value::Bool(true)) => "TRUE".to_string(),
+        Some(Value::Bool(false)) => "FALSE".to_string(),
+        Some(Value::Number(number)) => number.to_string(),
+        Some(Value::String(text)) => sql_string(text),
+        Some(nested_value) => sql_string(&nested_value.to_string()),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map, Value};
+
+    use crate::exporters::sql::json_stat_to_sql_insert;
+    use crate::json_stat_extractor::extract_stat_from_json_value;
+
+    fn rows(json_value: Value) -> Vec<Map<String, Value>> {
+        return json_value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| row.as_object().unwrap().clone())
+            .collect();
+    }
+
+    #[test]
+    fn it_should_insert_one_value_per_attribute_and_escape_strings() {
+        let document = json!([
+            {"id": 1, "name": "O'Brien", "tags": ["a"]},
+            {"id": 2, "active": true, "name": null}
+        ]);
+        let stat = extract_stat_from_json_value(document.clone());
+        let insert = json_stat_to_sql_insert(&stat, "users", &rows(document));
+        assert_eq!(
+            insert,
+            "INSERT INTO \"users\" (\"id\", \"name\", \"tags\", \"active\") VALUES\n\
+             (1, 'O''Brien', '[\"a\"]', NULL),\n\
+             (2, NULL, NULL, TRUE);\n"
+        );
+    }
+
+    #[test]
+    fn it_should_split_large_inserts() {
+        let document = Value::Array((0..1001).map(|id| json!({"id": id})).collect());
+        let stat = extract_stat_from_json_value(document.clone());
+        let insert = json_stat_to_sql_insert(&stat, "ids", &rows(document));
+        assert_eq!(insert.matches("INSERT INTO").count(), 2);
+        assert!(insert.ends_with("VALUES\n(1000);\n"));
+    }
+}
@@ -0,0 +1,68 @@
+use crate::json_stat_extractor::JsonStat::{ArrayStat, ObjStat, ValStat};
+use crate::json_stat_extractor::{json_stat_field_count, JsonStat};
+
+// one line for logs, the sizes of an array are the sizes of its items
+pub fn json_stat_to_summary_string(json_stat: &JsonStat) -> String {
+    return match json_stat {
+        ObjStat(obj_stat) => format!(
+            "Object: {} fields, {} bytes avg, {}–{} bytes range",
+            json_stat_field_count(json_stat),
+            obj_stat.size,
+            obj_stat.min_size,
+            obj_stat.max_size
+        ),
+        ArrayStat(array_stat) => {
+            let items_size = array_stat
+                .size
+                .saturating_sub(array_stat.count.saturating_sub(1) + 2);
+            let avg_item_size = items_size.checked_div(array_stat.count).unwrap_or(0);
+            format!(
+                "Array: {} items, {} bytes avg, {}–{} bytes range, {} distinct fields",
+                array_stat.count,
+                avg_item_size,
+                array_stat.min_size,
+                array_stat.max_size,
+                json_stat_field_count(json_stat)
+            )
+        }
+        ValStat(val_stat) => format!(
+            "Scalar: {} bytes avg, {}–{} bytes range",
+            val_stat.size, val_stat.min_size, val_stat.max_size
+        ),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::exporters::summary::json_stat_to_summary_string;
+    use crate::json_stat_extractor::extract_stat_from_json_value;
+
+    #[test]
+    fn it_should_summarize_an_object() {
+        let json_stat = extract_stat_from_json_value(json!({"a": 1, "b": "xy", "c": null}));
+        assert_eq!(
+            json_stat_to_summary_string(&json_stat),
+            "Object: 3 fields, 25 bytes avg, 25–25 bytes range"
+        );
+    }
+
+    #[test]
+    fn it_should_summarize_an_array() {
+        let json_stat = extract_stat_from_json_value(json!([{"a": 1}, {"a": 1, "b": 22}]));
+        assert_eq!(
+            json_stat_to_summary_string(&json_stat),
+            "Array: 2 items, 10 bytes avg, 7–14 bytes range, 2 distinct fields"
+        );
+    }
+
+    #[test]
+    fn it_should_summarize_a_scalar() {
+        let json_stat = extract_stat_from_json_value(json!("abc"));
+        assert_eq!(
+            json_stat_to_summary_string(&json_stat),
+            "Scalar: 5 bytes avg, 5–5 bytes range"
+        );
+    }
+}
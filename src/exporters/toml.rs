@@ -0,0 +1,65 @@
+use crate::json_stat_error::JsonStatError;
+use crate::json_stat_extractor::JsonStat;
+
+// TOML has no top-level enum, the stat is written as a table named after its kind
+// (`[ObjStat]`, `[ArrayStat]` or `[ValStat]`) and the attributes as arrays of tables. TOML
+// integers being 64-bit signed, a size above i64::MAX cannot be written.
+pub fn json_stat_to_toml(json_stat: &JsonStat) -> Result<String, JsonStatError> {
+    return ::toml::to_string_pretty(json_stat)
+        .map_err(|error| JsonStatError::Serialization(error.to_string()));
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use serde_json::json;
+
+    use crate::exporters::toml::json_stat_to_toml;
+    use crate::json_stat_error::JsonStatError;
+    use crate::json_stat_extractor::{
+        extract_stat_from_json_value, json_stat_size, JsonStat, JsonValStat,
+    };
+
+    #[test]
+    fn it_should_parse_toml_output_back_with_the_same_size() {
+        let json_stat = extract_stat_from_json_value(json!({
+            "name": "jsonstat",
+            "tags": [{"label": "cli"}, {"label": "stats"}],
+            "version": 1
+        }));
+        let toml_output = json_stat_to_toml(&json_stat).unwrap();
+        let parsed_stat: JsonStat = ::toml::from_str(&toml_output).unwrap();
+        assert_eq!(json_stat_size(&parsed_stat), json_stat_size(&json_stat));
+        assert_eq!(
+            serde_json::to_value(&parsed_stat).unwrap(),
+            serde_json::to_value(&json_stat).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_parse_back_toml_output_of_heterogeneous_values() {
+        let json_stat = extract_stat_from_json_value(json!({
+            "matrix": [[1, 2], [3]],
+            "none": null,
+            "items": [{"id": null, "children": [{"name": "1"}]}, {"id": 2}],
+            "mixed": [1, "a", null, true, 1.5]
+        }));
+        let toml_output = json_stat_to_toml(&json_stat).unwrap();
+        let parsed_stat: JsonStat = ::toml::from_str(&toml_output).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed_stat).unwrap(),
+            serde_json::to_value(&json_stat).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_fail_on_sizes_out_of_the_toml_integer_range() {
+        let json_stat = JsonStat::ValStat(JsonValStat::new(usize::MAX, 0, usize::MAX));
+        match json_stat_to_toml(&json_stat) {
+            Err(JsonStatError::Serialization(_)) => {}
+            _ => {
+                assert!(false);
+            }
+        }
+    }
+}
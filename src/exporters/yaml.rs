@@ -0,0 +1,40 @@
+use crate::json_stat_error::JsonStatError;
+use crate::json_stat_extractor::JsonStat;
+
+// the kind of the stat is written as a YAML tag (`!ObjStat`, `!ArrayStat` or `!ValStat`)
+pub fn stats_to_yaml(stat: &JsonStat) -> Result<String, JsonStatError> {
+    return serde_yaml::to_string(stat)
+        .map_err(|error| JsonStatError::Serialization(error.to_string()));
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::exporters::yaml::stats_to_yaml;
+    use crate::json_stat_extractor::{extract_stat_from_json_value, JsonStat};
+
+    #[test]
+    fn it_should_parse_yaml_output_back_to_the_same_stat() {
+        let json_stat = extract_stat_from_json_value(json!({
+            "name": "jsonstat",
+            "tags": [{"label": "cli"}, {"label": "stats"}],
+            "version": 1
+        }));
+        let yaml_output = stats_to_yaml(&json_stat).unwrap();
+        let parsed_stat: JsonStat = serde_yaml::from_str(&yaml_output).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed_stat).unwrap(),
+            serde_json::to_value(&json_stat).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_write_attribute_names_without_json_syntax() {
+        let json_stat = extract_stat_from_json_value(json!({"user_id": 1}));
+        let yaml_output = stats_to_yaml(&json_stat).unwrap();
+        assert!(yaml_output.contains("name: user_id"));
+        assert!(!yaml_output.contains("\"name\""));
+        assert!(!yaml_output.contains('{'));
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hasher;
+
+use serde_json::Value;
+
+use crate::json_stat_extractor::{extract_stat_from_json_value, JsonStat};
+
+// stats of already seen documents by the hash of their JSON bytes, the bytes being kept next to
+// the stat so that a document whose hash collides with another one gets its own stat. With a
+// capacity, the least recently used stat is evicted first.
+pub struct JsonStatCache {
+    inner: HashMap<u64, (Vec<u8>, JsonStat)>,
+    capacity: Option<usize>,
+    recently_used_hashes: VecDeque<u64>,
+    computation_count: usize,
+}
+
+impl JsonStatCache {
+    pub fn new() -> Self {
+        JsonStatCache {
+            inner: HashMap::new(),
+            capacity: None,
+            recently_used_hashes: VecDeque::new(),
+            computation_count: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        JsonStatCache {
+            capacity: Some(capacity.max(1)),
+            ..JsonStatCache::new()
+        }
+    }
+
+    pub fn get_or_compute(&mut self, value: Value) -> &JsonStat {
+        let json_bytes = serde_json::to_vec(&value).unwrap();
+        let hash = json_bytes_hash(&json_bytes);
+        let is_cached = self
+            .inner
+            .get(&hash)
+            .is_some_and(|(cached_bytes, _)| *cached_bytes == json_bytes);
+        if !is_cached {
+            if !self.inner.contains_key(&hash) && self.capacity == Some(self.inner.len()) {
+                if let Some(evicted_hash) = self.recently_used_hashes.pop_front() {
+                    self.inner.remove(&evicted_hash);
+                }
+            }
+            self.inner
+                .insert(hash, (json_bytes, extract_stat_from_json_value(value)));
+            self.computation_count += 1;
+        }
+        self.recently_used_hashes
+            .retain(|used_hash| *used_hash != hash);
+        self.recently_used_hashes.push_back(hash);
+        return &self.inner[&hash].1;
+    }
+
+    /// Number of stats computed so far, the documents found in the cache not being counted.
+    pub fn computation_count(&self) -> usize {
+        return self.computation_count;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.inner.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.inner.is_empty();
+    }
+}
+
+impl Default for JsonStatCache {
+    fn default() -> Self {
+        JsonStatCache::new()
+    }
+}
+
+fn json_bytes_hash(json_bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(json_bytes);
+    return hasher.finish();
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::json_stat_cache::{json_bytes_hash, JsonStatCache};
+    use crate::json_stat_extractor::{extract_stat_from_json_value, json_stat_size};
+
+    #[test]
+    fn it_should_compute_the_stat_of_a_document_once() {
+        let mut cache = JsonStatCache::new();
+        let size = json_stat_size(cache.get_or_compute(json!({"id": 1, "name": "test"})));
+        let cached_size = json_stat_size(cache.get_or_compute(json!({"id": 1, "name": "test"})));
+        assert_eq!(cached_size, size);
+        assert_eq!(cache.computation_count(), 1);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn it_should_compute_the_stat_of_a_document_whose_hash_collides() {
+        let mut cache = JsonStatCache::new();
+        let document = json!({"id": 1});
+        let hash = json_bytes_hash(&serde_json::to_vec(&document).unwrap());
+        cache.inner.insert(
+            hash,
+            (
+                br#"{"other":"document"}"#.to_vec(),
+                extract_stat_from_json_value(json!({"other": "document"})),
+            ),
+        );
+        let size = json_stat_size(cache.get_or_compute(document));
+        assert_eq!(size, 8);
+        assert_eq!(cache.computation_count(), 1);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn it_should_evict_the_least_recently_used_stat() {
+        let mut cache = JsonStatCache::with_capacity(2);
+        cache.get_or_compute(json!({"a": 1}));
+        cache.get_or_compute(json!({"b": 1}));
+        cache.get_or_compute(json!({"a": 1}));
+        cache.get_or_compute(json!({"c": 1}));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.computation_count(), 3);
+        cache.get_or_compute(json!({"a": 1}));
+        assert_eq!(cache.computation_count(), 3);
+        cache.get_or_compute(json!({"b": 1}));
+        assert_eq!(cache.computation_count(), 4);
+    }
+}
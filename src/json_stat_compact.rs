@@ -0,0 +1,250 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::json_stat_extractor::JsonStat::{ArrayStat, ObjStat, ValStat};
+use crate::json_stat_extractor::{JsonArrayStat, JsonAttrStat, JsonObjStat, JsonStat, JsonValStat};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompactStatKind {
+    Val,
+    Obj,
+    Array,
+}
+
+// the sizes and counts of a stat only, capped at u32::MAX, the value tracking (top values,
+// distinct values, percentiles...) being left out
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactStat {
+    pub(crate) kind: CompactStatKind,
+    pub(crate) size: u32,
+    /// Number of aggregated objects or items, 1 for a value.
+    pub(crate) count: u32,
+    pub(crate) max_size: u32,
+    pub(crate) min_size: u32,
+    pub(crate) attributes: Box<[CompactAttrStat]>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactAttrStat {
+    /// Name shared with the other attributes of the same name through the string pool.
+    pub(crate) name: Arc<str>,
+    pub(crate) size: u32,
+    pub(crate) count: u32,
+    pub(crate) total_size: u32,
+    pub(crate) max_size: u32,
+    pub(crate) min_size: u32,
+    pub(crate) values: Box<[CompactStat]>,
+}
+
+// the attribute names already met, an attribute name being allocated once for all the stats
+// compacted with the same pool
+#[derive(Default)]
+pub struct StringPool {
+    names: HashSet<Arc<str>>,
+}
+
+impl StringPool {
+    pub fn new() -> Self {
+        StringPool::default()
+    }
+
+    fn intern(&mut self, name: &str) -> Arc<str> {
+        if let Some(interned_name) = self.names.get(name) {
+            return interned_name.clone();
+        }
+        let interned_name: Arc<str> = Arc::from(name);
+        self.names.insert(interned_name.clone());
+        return interned_name;
+    }
+}
+
+// a stat compacted with its own string pool, see compact_stat_with_pool to share the attribute
+// names across a batch of stats
+pub fn compact_stat(stat: JsonStat) -> CompactStat {
+    return compact_stat_with_pool(stat, &mut StringPool::new());
+}
+
+pub fn compact_stat_with_pool(stat: JsonStat, pool: &mut StringPool) -> CompactStat {
+    let (kind, size, count, max_size, min_size, attributes) = match stat {
+        ValStat(vs) => (
+            CompactStatKind::Val,
+            vs.size,
+            1,
+            vs.max_size,
+            vs.min_size,
+            vec![],
+        ),
+        ObjStat(vs) => (
+            CompactStatKind::Obj,
+            vs.size,
+            vs.count,
+            vs.max_size,
+            vs.min_size,
+            vs.attributes,
+        ),
+        ArrayStat(vs) => (
+            CompactStatKind::Array,
+            vs.size,
+            vs.count,
+            vs.max_size,
+            vs.min_size,
+            vs.attributes,
+        ),
+    };
+    return CompactStat {
+        kind,
+        size: capped(size),
+        count: capped(count),
+        max_size: capped(max_size),
+        min_size: capped(min_size),
+        attributes: attributes
+            .into_iter()
+            .map(|attr_stat| compact_attr_stat(attr_stat, pool))
+            .collect(),
+    };
+}
+
+fn compact_attr_stat(attr_stat: JsonAttrStat, pool: &mut StringPool) -> CompactAttrStat {
+    return CompactAttrStat {
+        name: pool.intern(&attr_stat.name),
+        size: capped(attr_stat.size),
+        count: capped(attr_stat.count),
+        total_size: capped(attr_stat.total_size),
+        max_size: capped(attr_stat.max_size),
+        min_size: capped(attr_stat.min_size),
+        values: attr_stat
+            .values
+            .into_iter()
+            .map(|value_stat| compact_stat_with_pool(value_stat, pool))
+            .collect(),
+    };
+}
+
+fn capped(figure: usize) -> u32 {
+    return u32::try_from(figure).unwrap_or(u32::MAX);
+}
+
+// the stat is rebuilt from its sizes and counts, the figures left out by compact_stat being
+// those of a stat built with the JsonAttrStat, JsonObjStat and JsonArrayStat constructors
+impl From<CompactStat> for JsonStat {
+    fn from(compact_stat: CompactStat) -> Self {
+        let size = compact_stat.size as usize;
+        let count = compact_stat.count as usize;
+        let max_size = compact_stat.max_size as usize;
+        let min_size = compact_stat.min_size as usize;
+        let attributes: Vec<JsonAttrStat> = compact_stat
+            .attributes
+            .into_vec()
+            .into_iter()
+            .map(JsonAttrStat::from)
+            .collect();
+        return match compact_stat.kind {
+            CompactStatKind::Val => ValStat(JsonValStat::new(size, min_size, max_size)),
+            CompactStatKind::Obj => ObjStat(JsonObjStat::new(
+                size, count, min_size, max_size, attributes,
+            )),
+            CompactStatKind::Array => ArrayStat(JsonArrayStat::new(
+                size, count, min_size, max_size, attributes,
+            )),
+        };
+    }
+}
+
+impl From<CompactAttrStat> for JsonAttrStat {
+    fn from(compact_attr_stat: CompactAttrStat) -> Self {
+        let mut attr_stat = JsonAttrStat::new(
+            &compact_attr_stat.name,
+            compact_attr_stat.size as usize,
+            compact_attr_stat.count as usize,
+            compact_attr_stat.min_size as usize,
+            compact_attr_stat.max_size as usize,
+        );
+        attr_stat.total_size = compact_attr_stat.total_size as usize;
+        attr_stat.values = compact_attr_stat
+            .values
+            .into_vec()
+            .into_iter()
+            .map(JsonStat::from)
+            .collect();
+        return attr_stat;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use crate::json_stat_compact::{
+        compact_stat, compact_stat_with_pool, CompactStat, CompactStatKind, StringPool,
+    };
+    use crate::json_stat_extractor::{
+        collect_attribute_names, extract_stat_from_json_value, json_stat_attributes,
+        json_stat_size, JsonStat, JsonValStat,
+    };
+    use crate::json_stat_flattener::collect_attribute_paths;
+
+    #[test]
+    fn it_should_keep_the_sizes_back_and_forth() {
+        let json_stat = extract_stat_from_json_value(json!({
+            "id": 1,
+            "users": [{"name": "a", "tags": ["x", "y"]}, {"name": "bc"}]
+        }));
+        let restored_stat: JsonStat = compact_stat(json_stat.clone()).into();
+        assert_eq!(json_stat_size(&restored_stat), json_stat_size(&json_stat));
+        assert_eq!(
+            collect_attribute_paths(&restored_stat),
+            collect_attribute_paths(&json_stat)
+        );
+        let attributes = json_stat_attributes(&json_stat);
+        let restored_attributes = json_stat_attributes(&restored_stat);
+        attributes
+            .iter()
+            .zip(restored_attributes)
+            .for_each(|(attr_stat, restored_attr_stat)| {
+                assert_eq!(restored_attr_stat.size, attr_stat.size);
+                assert_eq!(restored_attr_stat.count, attr_stat.count);
+                assert_eq!(restored_attr_stat.total_size, attr_stat.total_size);
+                assert_eq!(restored_attr_stat.min_size, attr_stat.min_size);
+                assert_eq!(restored_attr_stat.max_size, attr_stat.max_size);
+                assert_eq!(
+                    restored_attr_stat
+                        .values
+                        .iter()
+                        .map(json_stat_size)
+                        .collect::<Vec<_>>(),
+                    attr_stat
+                        .values
+                        .iter()
+                        .map(json_stat_size)
+                        .collect::<Vec<_>>()
+                );
+            });
+    }
+
+    #[test]
+    fn it_should_share_the_attribute_names_of_a_batch() {
+        let mut pool = StringPool::new();
+        let compact_stats: Vec<CompactStat> = vec![json!({"id": 1}), json!([{"id": 2}])]
+            .into_iter()
+            .map(|value| compact_stat_with_pool(extract_stat_from_json_value(value), &mut pool))
+            .collect();
+        assert!(Arc::ptr_eq(
+            &compact_stats[0].attributes[0].name,
+            &compact_stats[1].attributes[0].name
+        ));
+        assert_eq!(compact_stats[1].kind, CompactStatKind::Array);
+        let restored_stat: JsonStat = compact_stats[1].clone().into();
+        assert_eq!(collect_attribute_names(&restored_stat), vec!["id"]);
+    }
+
+    #[test]
+    fn it_should_cap_the_sizes_at_u32_max() {
+        let json_stat = JsonStat::ValStat(JsonValStat::new(usize::MAX, 4, usize::MAX));
+        let compact_stat = compact_stat(json_stat);
+        assert_eq!(compact_stat.size, u32::MAX);
+        assert_eq!(compact_stat.min_size, 4);
+        assert_eq!(compact_stat.max_size, u32::MAX);
+    }
+}
@@ -0,0 +1,569 @@
+use std::collections::BTreeMap;
+use std::mem::discriminant;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::json_stat_extractor::JsonStat::{ArrayStat, ObjStat, ValStat};
+use crate::json_stat_extractor::{
+    json_stat_attributes, json_stat_level_attributes, json_stat_size, merge_stats, JsonArrayStat,
+    JsonAttrStat, JsonObjStat, JsonStat, JsonValStat,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonAttrChange {
+    Added,
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+// the sizes of an attribute are the sums of its value sizes, 0 on the side missing the attribute
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JsonAttrDelta {
+    pub(crate) name: String,
+    pub(crate) change: JsonAttrChange,
+    pub(crate) baseline_size: usize,
+    pub(crate) current_size: usize,
+    pub(crate) delta: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JsonStatDelta {
+    pub(crate) baseline_size: usize,
+    pub(crate) current_size: usize,
+    pub(crate) delta: i64,
+    pub(crate) attributes: Vec<JsonAttrDelta>,
+}
+
+// compares the top level attributes by name, in alphabetical order, the attributes of the arrays
+// nested as items of a top level array being top level attributes too, their sizes added to the
+// ones of the same name
+pub fn diff_json_stats(baseline_stat: &JsonStat, current_stat: &JsonStat) -> JsonStatDelta {
+    let mut attr_sizes: BTreeMap<&str, (Option<usize>, Option<usize>)> = BTreeMap::new();
+    json_stat_level_attributes(baseline_stat)
+        .into_iter()
+        .for_each(|attr_stat| {
+            let baseline_size = &mut attr_sizes.entry(&attr_stat.name).or_default().0;
+            *baseline_size = Some(baseline_size.unwrap_or(0) + attr_stat.total_size)
+        });
+    json_stat_level_attributes(current_stat)
+        .into_iter()
+        .for_each(|attr_stat| {
+            let current_size = &mut attr_sizes.entry(&attr_stat.name).or_default().1;
+            *current_size = Some(current_size.unwrap_or(0) + attr_stat.total_size)
+        });
+    let attributes = attr_sizes
+        .into_iter()
+        .map(|(name, (baseline_size, current_size))| {
+            let change = match (baseline_size, current_size) {
+                (None, _) => JsonAttrChange::Added,
+                (_, None) => JsonAttrChange::Removed,
+                (baseline_size, current_size) if baseline_size == current_size => {
+                    JsonAttrChange::Unchanged
+                }
+                _ => JsonAttrChange::Changed,
+            };
+            let baseline_size = baseline_size.unwrap_or(0);
+            let current_size = current_size.unwrap_or(0);
+            return JsonAttrDelta {
+                name: name.to_string(),
+                change,
+                baseline_size,
+                current_size,
+                delta: size_delta(baseline_size, current_size),
+            };
+        })
+        .collect();
+    let baseline_size = json_stat_size(baseline_stat);
+    let current_size = json_stat_size(current_stat);
+    return JsonStatDelta {
+        baseline_size,
+        current_size,
+        delta: size_delta(baseline_size, current_size),
+        attributes,
+    };
+}
+
+// Jaccard similarity of the top level attribute names, each attribute found in both stats
+// counting for the similarity of its sizes instead of 1: the mean of the ratios of the smaller to
+// the larger average, min and max sizes. 1.0 for identical stats, 0.0 without common attribute,
+// two stats without attributes are compared by their sizes.
+pub fn json_stat_stability_score(stat1: &JsonStat, stat2: &JsonStat) -> f64 {
+    let attributes1 = json_stat_attributes(stat1);
+    let attributes2 = json_stat_attributes(stat2);
+    if attributes1.is_empty() && attributes2.is_empty() {
+        return size_similarity(json_stat_size(stat1), json_stat_size(stat2));
+    }
+    let common_similarity: f64 = attributes1
+        .iter()
+        .filter_map(|attr_stat1| {
+            attributes2
+                .iter()
+                .find(|attr_stat2| attr_stat2.name == attr_stat1.name)
+                .map(|attr_stat2| attr_size_similarity(attr_stat1, attr_stat2))
+        })
+        .sum();
+    let common_count = attributes1
+        .iter()
+        .filter(|attr_stat1| {
+            attributes2
+                .iter()
+                .any(|attr_stat2| attr_stat2.name == attr_stat1.name)
+        })
+        .count();
+    let union_count = attributes1.len() + attributes2.len() - common_count;
+    return common_similarity / union_count as f64;
+}
+
+// the attributes found in both stats, in the order of the first one, with the sizes, percentiles,
+// counts and ratios averaged between both stats, the types and top values kept when found in
+// both and the values of each kind (object, array, scalar) merged then intersected. The figures
+// which cannot be merged, like the monotonicity, the distinct lengths, the item sizes or the
+// tuple types when they differ, are left out. Two stats of different kinds, or two value stats,
+// give a value stat of the average sizes. The arrays nested in two arrays and their items of same
+// index are intersected too.
+pub fn json_stat_intersection(stat1: &JsonStat, stat2: &JsonStat) -> JsonStat {
+    return match (stat1, stat2) {
+        (ObjStat(obj_stat1), ObjStat(obj_stat2)) => ObjStat(JsonObjStat::new(
+            average(obj_stat1.size, obj_stat2.size),
+            average(obj_stat1.count, obj_stat2.count),
+            average(obj_stat1.min_size, obj_stat2.min_size),
+            average(obj_stat1.max_size, obj_stat2.max_size),
+            common_attributes(&obj_stat1.attributes, &obj_stat2.attributes),
+        )),
+        (ArrayStat(array_stat1), ArrayStat(array_stat2)) => {
+            ArrayStat(array_stat_intersection(array_stat1, array_stat2))
+        }
+        (stat1, stat2) => {
+            let (min_size1, max_size1) = stat_size_range(stat1);
+            let (min_size2, max_size2) = stat_size_range(stat2);
+            ValStat(JsonValStat::new(
+                average(json_stat_size(stat1), json_stat_size(stat2)),
+                average(min_size1, min_size2),
+                average(max_size1, max_size2),
+            ))
+        }
+    };
+}
+
+fn array_stat_intersection(
+    array_stat1: &JsonArrayStat,
+    array_stat2: &JsonArrayStat,
+) -> JsonArrayStat {
+    let mut array_stat = JsonArrayStat::new(
+        average(array_stat1.size, array_stat2.size),
+        average(array_stat1.count, array_stat2.count),
+        average(array_stat1.min_size, array_stat2.min_size),
+        average(array_stat1.max_size, array_stat2.max_size),
+        common_attributes(&array_stat1.attributes, &array_stat2.attributes),
+    )
+    .with_attributes_per_element(
+        average_ratio(
+            array_stat1.avg_attributes_per_element,
+            array_stat2.avg_attributes_per_element,
+        ),
+        average(
+            array_stat1.max_attributes_per_element,
+            array_stat2.max_attributes_per_element,
+        ),
+    )
+    .with_quartile_sizes(
+        average(array_stat1.p25_size, array_stat2.p25_size),
+        average(array_stat1.p50_size, array_stat2.p50_size),
+        average(array_stat1.p75_size, array_stat2.p75_size),
+    )
+    .with_tail_sizes(
+        average(array_stat1.p90_size, array_stat2.p90_size),
+        average(array_stat1.p95_size, array_stat2.p95_size),
+        average(array_stat1.p99_size, array_stat2.p99_size),
+    )
+    .with_std_size(average_ratio(array_stat1.std_size, array_stat2.std_size));
+    if array_stat1.looks_like_tuple
+        && array_stat2.looks_like_tuple
+        && array_stat1.tuple_types == array_stat2.tuple_types
+    {
+        array_stat.looks_like_tuple = true;
+        array_stat.tuple_types = array_stat1.tuple_types.clone();
+    }
+    array_stat.item_type_counts =
+        common_counts(&array_stat1.item_type_counts, &array_stat2.item_type_counts);
+    array_stat.indexed_items = array_stat1
+        .indexed_items
+        .iter()
+        .zip(&array_stat2.indexed_items)
+        .map(|(item_stat1, item_stat2)| json_stat_intersection(item_stat1, item_stat2))
+        .collect();
+    array_stat.nested_array_stats = match (
+        &array_stat1.nested_array_stats,
+        &array_stat2.nested_array_stats,
+    ) {
+        (Some(nested_array_stat1), Some(nested_array_stat2)) => Some(Box::new(
+            array_stat_intersection(nested_array_stat1, nested_array_stat2),
+        )),
+        _ => None,
+    };
+    return array_stat;
+}
+
+fn common_attributes(
+    attributes1: &[JsonAttrStat],
+    attributes2: &[JsonAttrStat],
+) -> Vec<JsonAttrStat> {
+    return attributes1
+        .iter()
+        .filter_map(|attr_stat1| {
+            attributes2
+                .iter()
+                .find(|attr_stat2| attr_stat2.name == attr_stat1.name)
+                .map(|attr_stat2| attr_stat_intersection(attr_stat1, attr_stat2))
+        })
+        .collect();
+}
+
+fn attr_stat_intersection(attr_stat1: &JsonAttrStat, attr_stat2: &JsonAttrStat) -> JsonAttrStat {
+    let mut attr_stat = JsonAttrStat::new(
+        &attr_stat1.name,
+        average(attr_stat1.size, attr_stat2.size),
+        average(attr_stat1.count, attr_stat2.count),
+        average(attr_stat1.min_size, attr_stat2.min_size),
+        average(attr_stat1.max_size, attr_stat2.max_size),
+    );
+    attr_stat.path = attr_stat1.path.clone();
+    attr_stat.total_size = average(attr_stat1.total_size, attr_stat2.total_size);
+    attr_stat.size_pct = average_ratio(attr_stat1.size_pct, attr_stat2.size_pct);
+    attr_stat.present_ratio = average_ratio(attr_stat1.present_ratio, attr_stat2.present_ratio);
+    attr_stat.numeric_string_count = average(
+        attr_stat1.numeric_string_count,
+        attr_stat2.numeric_string_count,
+    );
+    attr_stat.string_truncated_count = average(
+        attr_stat1.string_truncated_count,
+        attr_stat2.string_truncated_count,
+    );
+    attr_stat.null_count = average(attr_stat1.null_count, attr_stat2.null_count);
+    attr_stat.type_counts = common_counts(&attr_stat1.type_counts, &attr_stat2.type_counts);
+    attr_stat.value_frequencies = attr_stat1
+        .value_frequencies
+        .iter()
+        .filter_map(|(value, count1)| {
+            attr_stat2
+                .value_frequencies
+                .get(value)
+                .map(|count2| (value.clone(), average(*count1, *count2)))
+        })
+        .collect();
+    attr_stat.top_values = attr_stat1
+        .top_values
+        .iter()
+        .filter_map(|(value, count1)| {
+            attr_stat2
+                .top_values
+                .iter()
+                .find(|(value2, _)| value2 == value)
+                .map(|(_, count2)| (value.clone(), average(*count1, *count2)))
+        })
+        .collect();
+    attr_stat.typed_top_values = attr_stat1
+        .typed_top_values
+        .iter()
+        .filter_map(|(value, type_name, count1)| {
+            attr_stat2
+                .typed_top_values
+                .iter()
+                .find(|(value2, type_name2, _)| value2 == value && type_name2 == type_name)
+                .map(|(_, _, count2)| (value.clone(), type_name.clone(), average(*count1, *count2)))
+        })
+        .collect();
+    if let (Some(distinct_values1), Some(distinct_values2)) =
+        (&attr_stat1.distinct_values, &attr_stat2.distinct_values)
+    {
+        let distinct_values = distinct_values1
+            .intersection(distinct_values2)
+            .cloned()
+            .collect();
+        attr_stat.distinct_values = Some(distinct_values);
+        attr_stat.unique_value_count = attr_stat
+            .distinct_values
+            .as_ref()
+            .map(|values| values.len());
+    }
+    attr_stat.values = common_values(&attr_stat1.values, &attr_stat2.values);
+    return attr_stat;
+}
+
+// the values of a kind are merged on both sides then intersected, whatever their order
+fn common_values(values1: &[JsonStat], values2: &[JsonStat]) -> Vec<JsonStat> {
+    let merged_values = |values: &[JsonStat], value_stat: &JsonStat| {
+        return values
+            .iter()
+            .filter(|other_value_stat| discriminant(*other_value_stat) == discriminant(value_stat))
+            .cloned()
+            .reduce(merge_stats);
+    };
+    return values1
+        .iter()
+        .unique_by(|value_stat| discriminant(*value_stat))
+        .filter_map(|value_stat| {
+            let merged_value1 = merged_values(values1, value_stat)?;
+            let merged_value2 = merged_values(values2, value_stat)?;
+            return Some(json_stat_intersection(&merged_value1, &merged_value2));
+        })
+        .collect();
+}
+
+// the types found in both counts with their counts averaged
+fn common_counts(
+    type_counts1: &BTreeMap<String, usize>,
+    type_counts2: &BTreeMap<String, usize>,
+) -> BTreeMap<String, usize> {
+    return type_counts1
+        .iter()
+        .filter_map(|(type_name, count1)| {
+            type_counts2
+                .get(type_name)
+                .map(|count2| (type_name.clone(), average(*count1, *count2)))
+        })
+        .collect();
+}
+
+fn stat_size_range(json_stat: &JsonStat) -> (usize, usize) {
+    return match json_stat {
+        ValStat(vs) => (vs.min_size, vs.max_size),
+        ObjStat(vs) => (vs.min_size, vs.max_size),
+        ArrayStat(vs) => (vs.min_size, vs.max_size),
+    };
+}
+
+fn average(size1: usize, size2: usize) -> usize {
+    return (size1 + size2) / 2;
+}
+
+fn average_ratio(ratio1: f64, ratio2: f64) -> f64 {
+    return (ratio1 + ratio2) / 2.0;
+}
+
+fn attr_size_similarity(attr_stat1: &JsonAttrStat, attr_stat2: &JsonAttrStat) -> f64 {
+    return (size_similarity(attr_stat1.size, attr_stat2.size)
+        + size_similarity(attr_stat1.min_size, attr_stat2.min_size)
+        + size_similarity(attr_stat1.max_size, attr_stat2.max_size))
+        / 3.0;
+}
+
+fn size_similarity(size1: usize, size2: usize) -> f64 {
+    if size1 == size2 {
+        return 1.0;
+    }
+    return size1.min(size2) as f64 / size1.max(size2) as f64;
+}
+
+fn size_delta(baseline_size: usize, current_size: usize) -> i64 {
+    return current_size as i64 - baseline_size as i64;
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use serde_json::json;
+
+    use crate::json_stat_diff::{
+        diff_json_stats, json_stat_intersection, json_stat_stability_score, JsonAttrChange,
+    };
+    use crate::json_stat_extractor::JsonStat::{ArrayStat, ValStat};
+    use crate::json_stat_extractor::{
+        collect_attribute_names, extract_stat_from_json_value, json_stat_attributes,
+        json_stat_nested_array, json_stat_size,
+    };
+
+    #[test]
+    fn it_should_report_a_positive_delta_for_a_grown_attribute() {
+        let baseline_stat = extract_stat_from_json_value(json!({"name": "a", "id": 1}));
+        let current_stat = extract_stat_from_json_value(json!({"name": "abcd", "id": 2}));
+        let stat_delta = diff_json_stats(&baseline_stat, &current_stat);
+        assert_eq!(stat_delta.delta, 3);
+        assert_eq!(stat_delta.attributes[0].name, "id");
+        assert_eq!(stat_delta.attributes[0].change, JsonAttrChange::Unchanged);
+        assert_eq!(stat_delta.attributes[1].name, "name");
+        assert_eq!(stat_delta.attributes[1].change, JsonAttrChange::Changed);
+        assert_eq!(stat_delta.attributes[1].delta, 3);
+    }
+
+    #[test]
+    fn it_should_report_added_and_removed_attributes() {
+        let baseline_stat = extract_stat_from_json_value(json!({"old": 12}));
+        let current_stat = extract_stat_from_json_value(json!({"new": 1}));
+        let stat_delta = diff_json_stats(&baseline_stat, &current_stat);
+        assert_eq!(stat_delta.attributes[0].change, JsonAttrChange::Added);
+        assert_eq!(stat_delta.attributes[0].delta, 1);
+        assert_eq!(stat_delta.attributes[1].change, JsonAttrChange::Removed);
+        assert_eq!(stat_delta.attributes[1].delta, -2);
+    }
+
+    #[test]
+    fn it_should_compare_the_attributes_of_nested_arrays() {
+        let baseline_stat = extract_stat_from_json_value(json!([
+            [{"secret_name": 1.123456789}],
+            [{"secret_name": 2}]
+        ]));
+        let current_stat = extract_stat_from_json_value(json!([[{"secret_name": 3, "id": 1}]]));
+        let stat_delta = diff_json_stats(&baseline_stat, &current_stat);
+        assert_eq!(stat_delta.attributes.len(), 2);
+        assert_eq!(stat_delta.attributes[0].name, "id");
+        assert_eq!(stat_delta.attributes[0].change, JsonAttrChange::Added);
+        assert_eq!(stat_delta.attributes[1].name, "secret_name");
+        assert_eq!(stat_delta.attributes[1].baseline_size, 12);
+        assert_eq!(stat_delta.attributes[1].delta, -11);
+    }
+
+    #[test]
+    fn it_should_score_the_stability_of_identical_and_disjoint_stats() {
+        let stat = extract_stat_from_json_value(json!([{"id": 1, "name": "a"}, {"id": 22}]));
+        assert_eq!(json_stat_stability_score(&stat, &stat), 1.0);
+        let other_stat = extract_stat_from_json_value(json!([{"key": 1, "label": "a"}]));
+        assert_eq!(json_stat_stability_score(&stat, &other_stat), 0.0);
+    }
+
+    #[test]
+    fn it_should_score_partially_overlapping_stats_between_bounds() {
+        let stat = extract_stat_from_json_value(json!({"id": 1, "name": "a"}));
+        let same_attributes_stat = extract_stat_from_json_value(json!({"id": 2, "name": "abcd"}));
+        let overlapping_stat = extract_stat_from_json_value(json!({"id": 1, "label": "a"}));
+        let same_attributes_score = json_stat_stability_score(&stat, &same_attributes_stat);
+        let overlapping_score = json_stat_stability_score(&stat, &overlapping_stat);
+        assert!(same_attributes_score > 0.0 && same_attributes_score < 1.0);
+        assert!(overlapping_score > 0.0 && overlapping_score < 1.0);
+        assert_eq!(overlapping_score, 1.0 / 3.0);
+    }
+
+    #[test]
+    fn it_should_keep_only_the_common_attributes() {
+        let stat1 = extract_stat_from_json_value(json!([
+            {"id": 1, "name": "ab", "age": 30, "address": {"city": "Paris", "zip": "75001"}}
+        ]));
+        let stat2 = extract_stat_from_json_value(json!([
+            {"id": 2, "name": "abcdef", "email": "a@b.c", "address": {"city": "Lyon"}}
+        ]));
+        let intersection = json_stat_intersection(&stat1, &stat2);
+        match &intersection {
+            ArrayStat(_) => {}
+            _ => {
+                assert!(false);
+            }
+        }
+        let mut attribute_names = collect_attribute_names(&intersection);
+        attribute_names.sort();
+        assert_eq!(attribute_names, vec!["address", "id", "name"]);
+        let attributes = json_stat_attributes(&intersection);
+        let name_attr_stat = attributes
+            .iter()
+            .find(|attr_stat| attr_stat.name == "name")
+            .unwrap();
+        assert_eq!(name_attr_stat.size, (4 + 8) / 2);
+        let address_attr_stat = attributes
+            .iter()
+            .find(|attr_stat| attr_stat.name == "address")
+            .unwrap();
+        assert_eq!(
+            collect_attribute_names(&address_attr_stat.values[0]),
+            vec!["city"]
+        );
+    }
+
+    #[test]
+    fn it_should_intersect_the_values_of_the_same_kind() {
+        let stat1 = extract_stat_from_json_value(json!([
+            {"meta": {"a": 1, "b": 2}},
+            {"meta": "x"}
+        ]));
+        let stat2 = extract_stat_from_json_value(json!([
+            {"meta": "yyy"},
+            {"meta": {"a": 3}}
+        ]));
+        let intersection = json_stat_intersection(&stat1, &stat2);
+        let meta_values = &json_stat_attributes(&intersection)[0].values;
+        assert_eq!(meta_values.len(), 2);
+        assert_eq!(collect_attribute_names(&meta_values[0]), vec!["a"]);
+        match &meta_values[1] {
+            ValStat(val_stat) => assert_eq!(val_stat.size, (3 + 5) / 2),
+            _ => {
+                assert!(false);
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_merge_or_leave_out_the_figures_of_both_stats() {
+        let stat1 = extract_stat_from_json_value(json!([
+            {"id": 1, "kind": "a"},
+            {"id": 2, "kind": "b"},
+            {"id": 3, "kind": "b"}
+        ]));
+        let stat2 = extract_stat_from_json_value(json!([
+            {"id": 30, "kind": "a"},
+            {"id": "x", "kind": "a"}
+        ]));
+        let intersection = json_stat_intersection(&stat1, &stat2);
+        match (&stat1, &stat2, &intersection) {
+            (ArrayStat(array_stat1), ArrayStat(array_stat2), ArrayStat(array_stat)) => {
+                assert_eq!(
+                    array_stat.p50_size,
+                    (array_stat1.p50_size + array_stat2.p50_size) / 2
+                );
+                assert_eq!(
+                    array_stat.std_size,
+                    (array_stat1.std_size + array_stat2.std_size) / 2.0
+                );
+                assert_eq!(array_stat.item_type_counts["object"], (3 + 2) / 2);
+            }
+            _ => {
+                assert!(false);
+            }
+        }
+        let attributes = json_stat_attributes(&intersection);
+        let id_attr_stat = &attributes[0];
+        assert_eq!(id_attr_stat.name, "id");
+        assert_eq!(
+            id_attr_stat.type_counts.keys().collect::<Vec<_>>(),
+            vec!["number"]
+        );
+        assert_eq!(id_attr_stat.is_monotonic_increasing, None);
+        assert!(id_attr_stat.top_values.is_empty());
+        let kind_attr_stat = &attributes[1];
+        assert_eq!(kind_attr_stat.top_values.len(), 1);
+        assert_eq!(kind_attr_stat.top_values[0].1, 1);
+        assert_eq!(kind_attr_stat.typed_top_values[0].2, 1);
+        assert_eq!(kind_attr_stat.unique_value_count, Some(1));
+    }
+
+    #[test]
+    fn it_should_intersect_the_nested_arrays() {
+        let stat1 = extract_stat_from_json_value(json!([
+            [{"secret_name": 1.123456789, "id": 1}],
+            [{"secret_name": 2}]
+        ]));
+        let stat2 = extract_stat_from_json_value(json!([[{"secret_name": 3, "label": "a"}]]));
+        let intersection = json_stat_intersection(&stat1, &stat2);
+        assert_eq!(collect_attribute_names(&intersection), vec!["secret_name"]);
+        let nested_array_stat = json_stat_nested_array(&intersection).unwrap();
+        assert_eq!(nested_array_stat.attributes[0].total_size, 6);
+        let stat3 = extract_stat_from_json_value(json!([{"secret_name": 3}]));
+        let intersection = json_stat_intersection(&stat1, &stat3);
+        assert!(json_stat_nested_array(&intersection).is_none());
+    }
+
+    #[test]
+    fn it_should_give_a_value_stat_for_mismatched_kinds() {
+        let stat1 = extract_stat_from_json_value(json!({"id": 1}));
+        let stat2 = extract_stat_from_json_value(json!("abcd"));
+        let intersection = json_stat_intersection(&stat1, &stat2);
+        match intersection {
+            ValStat(val_stat) => {
+                assert_eq!(val_stat.size, (json_stat_size(&stat1) + 6) / 2);
+            }
+            _ => {
+                assert!(false);
+            }
+        }
+    }
+}
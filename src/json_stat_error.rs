@@ -0,0 +1,55 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsonStatError {
+    /// the jq filter cannot be parsed or fails on the document
+    JqFilter(String),
+    /// the input ends in the middle of a JSON value, like a file cut by `--limit`
+    PartialInput(String),
+    InvalidJson(String),
+    /// the stat cannot be written in the output format
+    Serialization(String),
+    /// a CSV stat file has an unterminated quote, a missing column or a size that is not a number
+    InvalidCsv(String),
+    /// the input cannot be read, like a corrupted gzip stream or a line which is not UTF-8
+    Io(String),
+}
+
+impl fmt::Display for JsonStatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            JsonStatError::JqFilter(message) => write!(f, "jq filter error: {message}"),
+            JsonStatError::PartialInput(message) => write!(f, "partial input: {message}"),
+            JsonStatError::InvalidJson(message) => write!(f, "invalid JSON: {message}"),
+            JsonStatError::Serialization(message) => write!(f, "serialization error: {message}"),
+            JsonStatError::InvalidCsv(message) => write!(f, "invalid CSV: {message}"),
+            JsonStatError::Io(message) => write!(f, "read error: {message}"),
+        };
+    }
+}
+
+impl JsonStatError {
+    // the same error located in a part of the input, like `line 3` of NDJSON content
+    pub fn at(self, location: &str) -> JsonStatError {
+        return match self {
+            JsonStatError::JqFilter(message) => {
+                JsonStatError::JqFilter(format!("{location}: {message}"))
+            }
+            JsonStatError::PartialInput(message) => {
+                JsonStatError::PartialInput(format!("{location}: {message}"))
+            }
+            JsonStatError::InvalidJson(message) => {
+                JsonStatError::InvalidJson(format!("{location}: {message}"))
+            }
+            JsonStatError::Serialization(message) => {
+                JsonStatError::Serialization(format!("{location}: {message}"))
+            }
+            JsonStatError::InvalidCsv(message) => {
+                JsonStatError::InvalidCsv(format!("{location}: {message}"))
+            }
+            JsonStatError::Io(message) => JsonStatError::Io(format!("{location}: {message}")),
+        };
+    }
+}
+
+impl std::error::Error for JsonStatError {}
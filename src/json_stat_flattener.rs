@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::json_stat_extractor::JsonStat::{ArrayStat, ObjStat, ValStat};
+use crate::json_stat_extractor::{json_stat_size, JsonArrayStat, JsonAttrStat, JsonStat};
+
+// nested attributes are named by their dotted path, items of arrays being noted with []
+// like in users[].name, and items of nested arrays with one [] per array like in
+// matrix[][].name, or with their index like in users[0].name for the items kept by
+// `JsonStatOptions::indexed_array_max_index`, the [] paths then being given only for the arrays
+// having more items
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JsonFlatAttrStat {
+    pub(crate) path: String,
+    pub(crate) size: usize,
+    pub(crate) count: usize,
+    pub(crate) max_size: usize,
+    pub(crate) min_size: usize,
+    /// JSON types of the values found at the path, empty for the scalar items of an array.
+    #[serde(skip)]
+    pub(crate) type_names: BTreeSet<String>,
+}
+
+const OBJECT_TYPE: &str = "object";
+const ARRAY_TYPE: &str = "array";
+
+pub fn flatten_json_stat(json_stat: &JsonStat) -> Vec<JsonFlatAttrStat> {
+    let mut flat_attr_stats: BTreeMap<String, JsonFlatAttrStat> = BTreeMap::new();
+    collect_flat_attr_stats(json_stat, "", &mut flat_attr_stats);
+    return flat_attr_stats.into_values().collect();
+}
+
+pub fn collect_attribute_paths(json_stat: &JsonStat) -> Vec<String> {
+    return flatten_json_stat(json_stat)
+        .into_iter()
+        .map(|flat_attr_stat| flat_attr_stat.path)
+        .collect();
+}
+
+fn collect_flat_attr_stats(
+    json_stat: &JsonStat,
+    path_prefix: &str,
+    flat_attr_stats: &mut BTreeMap<String, JsonFlatAttrStat>,
+) {
+    match json_stat {
+        ValStat(_) => {}
+        ObjStat(vs) => collect_flat_attributes(&vs.attributes, path_prefix, flat_attr_stats),
+        ArrayStat(vs) => collect_flat_array_attributes(vs, path_prefix, flat_attr_stats),
+    }
+}
+
+// the items of the arrays nested in the array add a [] to the path of its items
+fn collect_flat_array_attributes(
+    array_stat: &JsonArrayStat,
+    path_prefix: &str,
+    flat_attr_stats: &mut BTreeMap<String, JsonFlatAttrStat>,
+) {
+    collect_flat_attributes(&array_stat.attributes, path_prefix, flat_attr_stats);
+    if let Some(nested_array_stat) = array_stat.nested_array_stats.as_deref() {
+        let nested_path_prefix = format!(
+            "{}[].",
+            path_prefix.strip_suffix('.').unwrap_or(path_prefix)
+        );
+        collect_flat_array_attributes(nested_array_stat, &nested_path_prefix, flat_attr_stats);
+    }
+}
+
+fn collect_flat_attributes(
+    attributes: &[JsonAttrStat],
+    path_prefix: &str,
+    flat_attr_stats: &mut BTreeMap<String, JsonFlatAttrStat>,
+) {
+    attributes.iter().for_each(|attr_stat| {
+        let attr_path = format!("{path_prefix}{}", attr_stat.name);
+        flat_attr_stats
+            .entry(attr_path.clone())
+            .and_modify(|flat_attr_stat| {
+                let total_size =
+                    flat_attr_stat.size * flat_attr_stat.count + attr_stat.size * attr_stat.count;
+                flat_attr_stat.count += attr_stat.count;
+                flat_attr_stat.size = total_size / flat_attr_stat.count;
+                flat_attr_stat.max_size = flat_attr_stat.max_size.max(attr_stat.max_size);
+                flat_attr_stat.min_size = flat_attr_stat.min_size.min(attr_stat.min_size);
+                flat_attr_stat
+                    .type_names
+                    .extend(attr_stat.type_counts.keys().cloned());
+            })
+            .or_insert(JsonFlatAttrStat {
+                path: attr_path.clone(),
+                size: attr_stat.size,
+                count: attr_stat.count,
+                max_size: attr_stat.max_size,
+                min_size: attr_stat.min_size,
+                type_names: attr_stat.type_counts.keys().cloned().collect(),
+            });
+        attr_stat
+            .values
+            .iter()
+            .for_each(|value_stat| match value_stat {
+                ArrayStat(array_stat) if !array_stat.indexed_items.is_empty() => {
+                    array_stat
+                        .indexed_items
+                        .iter()
+                        .enumerate()
+                        .for_each(|(index, item_stat)| {
+                            let item_path = format!("{attr_path}[{index}]");
+                            add_flat_item_stat(item_stat, &item_path, flat_attr_stats);
+                            collect_flat_attr_stats(
+                                item_stat,
+                                &format!("{item_path}."),
+                                flat_attr_stats,
+                            )
+                        });
+                    if array_stat.count > array_stat.indexed_items.len() {
+                        collect_flat_attr_stats(
+                            value_stat,
+                            &format!("{attr_path}[]."),
+                            flat_attr_stats,
+                        )
+                    }
+                }
+                ArrayStat(_) => {
+                    collect_flat_attr_stats(value_stat, &format!("{attr_path}[]."), flat_attr_stats)
+                }
+                _ => collect_flat_attr_stats(value_stat, &format!("{attr_path}."), flat_attr_stats),
+            });
+    });
+}
+
+fn add_flat_item_stat(
+    item_stat: &JsonStat,
+    item_path: &str,
+    flat_attr_stats: &mut BTreeMap<String, JsonFlatAttrStat>,
+) {
+    let item_size = json_stat_size(item_stat);
+    let item_type_name = match item_stat {
+        ValStat(_) => None,
+        ObjStat(_) => Some(OBJECT_TYPE.to_string()),
+        ArrayStat(_) => Some(ARRAY_TYPE.to_string()),
+    };
+    flat_attr_stats
+        .entry(item_path.to_string())
+        .and_modify(|flat_attr_stat| {
+            let total_size = flat_attr_stat.size * flat_attr_stat.count + item_size;
+            flat_attr_stat.count += 1;
+            flat_attr_stat.size = total_size / flat_attr_stat.count;
+            flat_attr_stat.max_size = flat_attr_stat.max_size.max(item_size);
+            flat_attr_stat.min_size = flat_attr_stat.min_size.min(item_size);
+            flat_attr_stat.type_names.extend(item_type_name.clone());
+        })
+        .or_insert(JsonFlatAttrStat {
+            path: item_path.to_string(),
+            size: item_size,
+            count: 1,
+            max_size: item_size,
+            min_size: item_size,
+            type_names: item_type_name.into_iter().collect(),
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::json_stat_extractor::{
+        extract_stat_from_json_value, extract_stat_from_json_with_options, JsonStatOptions,
+    };
+    use crate::json_stat_flattener::{collect_attribute_paths, flatten_json_stat};
+
+    #[test]
+    fn it_should_provide_dotted_paths_of_nested_attributes() {
+        let result = extract_stat_from_json_value(json!({
+            "id": 1,
+            "user": {"name": "test", "address": {"city": "Paris"}},
+            "tags": [{"label": "a"}, {"label": "bb"}]
+        }));
+        assert_eq!(
+            collect_attribute_paths(&result),
+            vec![
+                "id",
+                "tags",
+                "tags[].label",
+                "user",
+                "user.address",
+                "user.address.city",
+                "user.name"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_provide_the_paths_of_nested_array_items() {
+        let result = extract_stat_from_json_value(json!([
+            [{"secret_name": 1.123456789}],
+            [{"secret_name": 2}]
+        ]));
+        let flat_attr_stats = flatten_json_stat(&result);
+        assert_eq!(flat_attr_stats.len(), 1);
+        assert_eq!(flat_attr_stats[0].path, "[].secret_name");
+        assert_eq!(flat_attr_stats[0].count, 2);
+        let result = extract_stat_from_json_value(json!({"matrix": [[{"x": 1}]]}));
+        assert_eq!(
+            collect_attribute_paths(&result),
+            vec!["matrix", "matrix[][].x"]
+        );
+    }
+
+    #[test]
+    fn it_should_merge_stats_of_a_path_found_in_several_values() {
+        let result = extract_stat_from_json_value(json!([{"a": {"b": "x"}}, {"a": {"b": "xyz"}}]));
+        let flat_attr_stats = flatten_json_stat(&result);
+        assert_eq!(flat_attr_stats.len(), 2);
+        let b_flat_attr_stat = flat_attr_stats.get(1).unwrap();
+        assert_eq!(b_flat_attr_stat.path, "a.b");
+        assert_eq!(b_flat_attr_stat.count, 2);
+        assert_eq!(b_flat_attr_stat.size, 4);
+        assert_eq!(b_flat_attr_stat.min_size, 3);
+        assert_eq!(b_flat_attr_stat.max_size, 5);
+    }
+
+    #[test]
+    fn it_should_provide_indexed_paths_of_the_first_array_items() {
+        let options = JsonStatOptions {
+            indexed_array_max_index: Some(1),
+            ..JsonStatOptions::default()
+        };
+        let result =
+            extract_stat_from_json_with_options(r#"{"items":["a","b"]}"#.as_bytes(), &options);
+        assert_eq!(
+            collect_attribute_paths(&result),
+            vec!["items", "items[0]", "items[1]"]
+        );
+        let result = extract_stat_from_json_with_options(
+            r#"{"users":[{"id":1},{"id":2},{"id":3}]}"#.as_bytes(),
+            &options,
+        );
+        assert_eq!(
+            collect_attribute_paths(&result),
+            vec![
+                "users",
+                "users[0]",
+                "users[0].id",
+                "users[1]",
+                "users[1].id",
+                "users[].id"
+            ]
+        );
+    }
+}
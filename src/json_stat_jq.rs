@@ -0,0 +1,72 @@
+use crate::json_stat_error::JsonStatError;
+use jaq_core::load::{Arena, File, Loader};
+use jaq_core::{Compiler, Ctx, RcIter};
+use jaq_json::Val;
+use serde_json::Value;
+
+// the values produced by a jq filter such as `.items[] | .user`, in the order of the filter outputs
+pub fn apply_jq_filter(value: Value, filter: &str) -> Result<Vec<Value>, JsonStatError> {
+    let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+    let arena = Arena::default();
+    let program = File {
+        code: filter,
+        path: (),
+    };
+    let modules = loader
+        .load(&arena, program)
+        .map_err(|_| JsonStatError::JqFilter(format!("cannot parse {filter}")))?;
+    let compiled_filter = Compiler::default()
+        .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+        .compile(modules)
+        .map_err(|_| JsonStatError::JqFilter(format!("cannot compile {filter}")))?;
+    let inputs = RcIter::new(core::iter::empty());
+    return compiled_filter
+        .run((Ctx::new([], &inputs), Val::from(value)))
+        .map(|output| {
+            output
+                .map(Value::from)
+                .map_err(|error| JsonStatError::JqFilter(format!("{filter}: {error}")))
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_stat_error::JsonStatError;
+    use crate::json_stat_extractor::JsonStat::ArrayStat;
+    use crate::json_stat_extractor::{extract_stat_from_json_value, JsonArrayStat};
+    use crate::json_stat_jq::apply_jq_filter;
+    use serde_json::json;
+
+    #[test]
+    #[allow(clippy::assertions_on_constants)]
+    fn it_should_select_the_array_at_the_jq_path() {
+        let outputs = apply_jq_filter(json!({"items": [1, 2, 3]}), ".items").unwrap();
+        assert_eq!(outputs, vec![json!([1, 2, 3])]);
+        match extract_stat_from_json_value(outputs[0].clone()) {
+            ArrayStat(JsonArrayStat { count, size, .. }) => {
+                assert_eq!(count, 3);
+                assert_eq!(size, 7);
+            }
+            _ => {
+                assert!(false);
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_produce_one_value_per_iterated_item() {
+        let outputs = apply_jq_filter(
+            json!({"items": [{"user": "a"}, {"user": "b"}]}),
+            ".items[] | .user",
+        )
+        .unwrap();
+        assert_eq!(outputs, vec![json!("a"), json!("b")]);
+    }
+
+    #[test]
+    fn it_should_reject_an_invalid_filter() {
+        let result = apply_jq_filter(json!({}), ".items[");
+        assert!(matches!(result, Err(JsonStatError::JqFilter(_))));
+    }
+}
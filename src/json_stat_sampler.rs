@@ -0,0 +1,42 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// reservoir sampling: every item has the same chance to be kept whatever the array length, the
+// first items fill the reservoir and each next item replaces a random one with a decreasing odds,
+// a seed gives the same sample on each run
+pub fn sample_items<T>(items: Vec<T>, sample_size: usize, seed: Option<u64>) -> Vec<T> {
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut reservoir: Vec<T> = Vec::with_capacity(sample_size.min(items.len()));
+    for (index, item) in items.into_iter().enumerate() {
+        if index < sample_size {
+            reservoir.push(item);
+        } else {
+            let replaced_index = rng.gen_range(0..=index);
+            if replaced_index < sample_size {
+                reservoir[replaced_index] = item;
+            }
+        }
+    }
+    return reservoir;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_stat_sampler::sample_items;
+
+    #[test]
+    fn it_should_keep_all_items_of_a_small_array() {
+        assert_eq!(sample_items(vec![1, 2, 3], 5, Some(1)), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn it_should_give_the_same_sample_with_the_same_seed() {
+        let items: Vec<usize> = (0..1000).collect();
+        let sample = sample_items(items.clone(), 10, Some(42));
+        assert_eq!(sample.len(), 10);
+        assert_eq!(sample, sample_items(items, 10, Some(42)));
+    }
+}
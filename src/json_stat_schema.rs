@@ -0,0 +1,204 @@
+use serde_json::{json, Map, Value};
+
+use crate::json_stat_extractor::{
+    extract_stat_from_json_value, merged_nested_stat, JsonAttrStat, JsonStat,
+};
+
+// a string without maxLength is assumed 20 bytes long with its double quotes
+const ASSUMED_STRING_LENGTH: usize = 18;
+const ASSUMED_INTEGER: i64 = 1000;
+const ASSUMED_NUMBER: f64 = 12.5;
+
+// the stat of a synthetic document following the schema: every property of an object is present,
+// a string has its maxLength up to 20 bytes and an array holds two items, one with every property
+// and one with the required properties only, so that the optional attributes of the items are
+// present in half of them
+pub fn json_stat_from_schema(schema: &Value) -> JsonStat {
+    return extract_stat_from_json_value(example_value(schema, true));
+}
+
+// a JSON Schema with the structure of the stat, every property being annotated with the
+// `x-avg-size`, `x-min-size`, `x-max-size` and `x-count` extensions, the types of a property
+// being the JSON types of its values (an integer is reported as a number)
+pub fn json_stat_to_json_schema_annotations(stat: &JsonStat) -> Value {
+    return match stat {
+        JsonStat::ObjStat(obj_stat) => json!({
+            "type": "object",
+            "properties": properties_schema(&obj_stat.attributes),
+        }),
+        JsonStat::ArrayStat(array_stat) if !array_stat.attributes.is_empty() => json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": properties_schema(&array_stat.attributes),
+            },
+        }),
+        JsonStat::ArrayStat(_) => json!({"type": "array"}),
+        JsonStat::ValStat(_) => json!({}),
+    };
+}
+
+fn properties_schema(attributes: &[JsonAttrStat]) -> Map<String, Value> {
+    return attributes
+        .iter()
+        .map(|attr_stat| (attr_stat.name.clone(), property_schema(attr_stat)))
+        .collect();
+}
+
+// the object and array values of a property are merged to describe its nested structure
+fn property_schema(attr_stat: &JsonAttrStat) -> Value {
+    let mut schema = match merged_nested_stat(attr_stat) {
+        Some(nested_stat) => json_stat_to_json_schema_annotations(&nested_stat),
+        None => json!({}),
+    };
+    let type_names: Vec<&String> = attr_stat.type_counts.keys().collect();
+    match type_names.as_slice() {
+        [] => {}
+        [type_name] => schema["type"] = json!(type_name),
+        _ => schema["type"] = json!(type_names),
+    }
+    schema["x-avg-size"] = json!(attr_stat.size);
+    schema["x-min-size"] = json!(attr_stat.min_size);
+    schema["x-max-size"] = json!(attr_stat.max_size);
+    schema["x-count"] = json!(attr_stat.count);
+    return schema;
+}
+
+fn example_value(schema: &Value, with_optional_properties: bool) -> Value {
+    return match schema_type(schema) {
+        "object" => {
+            let required: Vec<&str> = schema["required"]
+                .as_array()
+                .map(|names| names.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let properties: Map<String, Value> = schema["properties"]
+                .as_object()
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .filter(|(name, _)| {
+                            with_optional_properties || required.contains(&name.as_str())
+                        })
+                        .map(|(name, property)| (name.clone(), example_value(property, true)))
+                        .collect()
+                })
+                .unwrap_or_default();
+            Value::Object(properties)
+        }
+        "array" => match schema.get("items") {
+            Some(items) => json!([example_value(items, true), example_value(items, false)]),
+            None => json!([]),
+        },
+        "string" => {
+            let length = schema["maxLength"]
+                .as_u64()
+                .map_or(ASSUMED_STRING_LENGTH, |max_length| {
+                    (max_length as usize).min(ASSUMED_STRING_LENGTH)
+                });
+            json!("x".repeat(length))
+        }
+        "integer" => json!(ASSUMED_INTEGER),
+        "number" => json!(ASSUMED_NUMBER),
+        "boolean" => json!(true),
+        _ => Value::Null,
+    };
+}
+
+// the first non null type of a type list, an object being assumed when properties are given
+fn schema_type(schema: &Value) -> &str {
+    return match &schema["type"] {
+        Value::String(type_name) => type_name,
+        Value::Array(type_names) => type_names
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|type_name| *type_name != "null")
+            .unwrap_or("null"),
+        _ if schema.get("properties").is_some() => "object",
+        _ => "null",
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::json_stat_extractor::{
+        extract_stat_from_json_value, json_stat_attributes, json_stat_paths,
+    };
+    use crate::json_stat_schema::{json_stat_from_schema, json_stat_to_json_schema_annotations};
+
+    #[test]
+    fn it_should_build_a_stat_with_the_structure_of_the_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer"},
+                "name": {"type": "string"},
+                "code": {"type": "string", "maxLength": 3},
+                "address": {"type": "object", "properties": {"city": {"type": "string"}}},
+                "users": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {"login": {"type": "string"}, "age": {"type": ["null", "integer"]}},
+                        "required": ["login"]
+                    }
+                }
+            },
+            "required": ["id"]
+        });
+        let document_stat = extract_stat_from_json_value(json!({
+            "id": 7,
+            "name": "jsonstat",
+            "code": "abc",
+            "address": {"city": "Paris"},
+            "users": [{"login": "a", "age": 20}]
+        }));
+        let schema_stat = json_stat_from_schema(&schema);
+        assert_eq!(
+            json_stat_paths(&schema_stat),
+            json_stat_paths(&document_stat)
+        );
+        let attributes = json_stat_attributes(&schema_stat);
+        let name_attribute = attributes.iter().find(|attr| attr.name == "name").unwrap();
+        assert_eq!(name_attribute.size, 20);
+        let code_attribute = attributes.iter().find(|attr| attr.name == "code").unwrap();
+        assert_eq!(code_attribute.size, 5);
+    }
+
+    #[test]
+    fn it_should_make_optional_item_properties_present_in_half_of_the_items() {
+        let schema = json!({
+            "type": "array",
+            "items": {
+                "properties": {"login": {"type": "string"}, "age": {"type": "integer"}},
+                "required": ["login"]
+            }
+        });
+        let schema_stat = json_stat_from_schema(&schema);
+        let attributes = json_stat_attributes(&schema_stat);
+        let login_attribute = attributes.iter().find(|attr| attr.name == "login").unwrap();
+        let age_attribute = attributes.iter().find(|attr| attr.name == "age").unwrap();
+        assert_eq!(login_attribute.present_ratio, 1.0);
+        assert_eq!(age_attribute.present_ratio, 0.5);
+    }
+
+    #[test]
+    fn it_should_annotate_the_schema_properties_with_their_sizes() {
+        let stat = extract_stat_from_json_value(json!([
+            {"id": 1, "name": "ab", "address": {"city": "Paris"}},
+            {"id": 22, "address": null}
+        ]));
+        let schema = json_stat_to_json_schema_annotations(&stat);
+        assert_eq!(schema["type"], "array");
+        let properties = &schema["items"]["properties"];
+        assert_eq!(properties["id"]["type"], "number");
+        assert_eq!(properties["id"]["x-count"], 2);
+        assert_eq!(properties["id"]["x-min-size"], 1);
+        assert_eq!(properties["id"]["x-max-size"], 2);
+        assert_eq!(properties["name"]["x-avg-size"], 4);
+        assert_eq!(properties["name"]["x-count"], 1);
+        assert_eq!(properties["address"]["type"], json!(["null", "object"]));
+        assert_eq!(properties["address"]["properties"]["city"]["x-max-size"], 7);
+    }
+}
@@ -0,0 +1,579 @@
+use sha2::{Digest, Sha256};
+
+use crate::json_stat_extractor::JsonStat::{ArrayStat, ObjStat, ValStat};
+use crate::json_stat_extractor::{
+    attr_stat_total_size, json_stat_attributes_mut, json_stat_size, visit_nested_array_stat_mut,
+    JsonAttrStat, JsonStat,
+};
+
+// the path of a stat is the JSON pointer of json_stat_paths, "" for the root, an attribute
+// aggregated from array items having one stat per value
+pub enum JsonStatPatchOperation<'a> {
+    SetSize {
+        path: &'a str,
+        size: usize,
+    },
+    AddAttribute {
+        path: &'a str,
+        attr: Box<JsonAttrStat>,
+    },
+    RemoveAttribute {
+        path: &'a str,
+        name: &'a str,
+    },
+    SetCount {
+        path: &'a str,
+        count: usize,
+    },
+}
+
+pub struct JsonStatPatch<'a> {
+    pub operations: Vec<JsonStatPatchOperation<'a>>,
+}
+
+// the total size of an attribute is the sum of the sizes of all its values, the parent sizes
+// are left untouched when attributes are removed
+pub fn filter_attributes_by_min_bytes(json_stat: &mut JsonStat, min_bytes: usize) {
+    visit_nested_array_stat_mut(json_stat, |nested_stat| {
+        filter_attributes_by_min_bytes(nested_stat, min_bytes)
+    });
+    if let Some(attributes) = json_stat_attributes_mut(json_stat) {
+        attributes.retain(|attr_stat| attr_stat_total_size(attr_stat) >= min_bytes);
+        attributes.iter_mut().for_each(|attr_stat| {
+            attr_stat
+                .values
+                .iter_mut()
+                .for_each(|value_stat| filter_attributes_by_min_bytes(value_stat, min_bytes))
+        });
+    }
+}
+
+// the attributes named old_name are renamed at every level of the stat
+pub fn rename_attribute(json_stat: &mut JsonStat, old_name: &str, new_name: &str) {
+    visit_nested_array_stat_mut(json_stat, |nested_stat| {
+        rename_attribute(nested_stat, old_name, new_name)
+    });
+    if let Some(attributes) = json_stat_attributes_mut(json_stat) {
+        attributes.iter_mut().for_each(|attr_stat| {
+            if attr_stat.name == old_name {
+                attr_stat.name = new_name.to_string();
+            }
+            attr_stat
+                .values
+                .iter_mut()
+                .for_each(|value_stat| rename_attribute(value_stat, old_name, new_name))
+        });
+    }
+}
+
+// the attributes named name are removed at every level, the parent sizes are left untouched
+pub fn omit_attribute(json_stat: &mut JsonStat, name: &str) {
+    visit_nested_array_stat_mut(json_stat, |nested_stat| omit_attribute(nested_stat, name));
+    if let Some(attributes) = json_stat_attributes_mut(json_stat) {
+        attributes.retain(|attr_stat| attr_stat.name != name);
+        attributes.iter_mut().for_each(|attr_stat| {
+            attr_stat
+                .values
+                .iter_mut()
+                .for_each(|value_stat| omit_attribute(value_stat, name))
+        });
+    }
+}
+
+// the attributes are sorted by name at every level, two stats of documents having their keys in
+// different orders being then equal
+pub fn normalize_stat(json_stat: &mut JsonStat) {
+    visit_nested_array_stat_mut(json_stat, normalize_stat);
+    if let Some(attributes) = json_stat_attributes_mut(json_stat) {
+        attributes.sort_by(|attr_stat, other_attr_stat| attr_stat.name.cmp(&other_attr_stat.name));
+        attributes
+            .iter_mut()
+            .for_each(|attr_stat| attr_stat.values.iter_mut().for_each(normalize_stat));
+    }
+}
+
+const EMPTY_VALUE_SIZE: usize = 2;
+const NUMBER_TYPE: &str = "number";
+
+// an attribute is empty when every value is `""`, `[]` or `{}`, that is 2 bytes without any
+// number, the empty attributes are removed at every level and the parent sizes left untouched
+pub fn omit_empty_attributes(json_stat: &mut JsonStat) {
+    visit_nested_array_stat_mut(json_stat, omit_empty_attributes);
+    if let Some(attributes) = json_stat_attributes_mut(json_stat) {
+        attributes.retain(|attr_stat| !is_empty_attribute(attr_stat));
+        attributes
+            .iter_mut()
+            .for_each(|attr_stat| attr_stat.values.iter_mut().for_each(omit_empty_attributes));
+    }
+}
+
+fn is_empty_attribute(attr_stat: &JsonAttrStat) -> bool {
+    return !attr_stat.values.is_empty()
+        && !attr_stat.type_counts.contains_key(NUMBER_TYPE)
+        && attr_stat
+            .values
+            .iter()
+            .all(|value_stat| json_stat_size(value_stat) == EMPTY_VALUE_SIZE);
+}
+
+// the path of every attribute is set to its JSON pointer, like in json_stat_paths, prefix being
+// the pointer of the stat, the attributes of the items kept by
+// `JsonStatOptions::indexed_array_max_index` having the index of their item, like in
+// /users/0/address
+pub fn enrich_with_paths(json_stat: &mut JsonStat, prefix: &str) {
+    visit_nested_array_stat_mut(json_stat, |nested_stat| {
+        enrich_with_paths(nested_stat, prefix)
+    });
+    if let ArrayStat(array_stat) = json_stat {
+        array_stat
+            .indexed_items
+            .iter_mut()
+            .enumerate()
+            .for_each(|(index, item_stat)| {
+                enrich_with_paths(item_stat, &format!("{prefix}/{index}"))
+            });
+    }
+    if let Some(attributes) = json_stat_attributes_mut(json_stat) {
+        attributes.iter_mut().for_each(|attr_stat| {
+            let escaped_name = attr_stat.name.replace('~', "~0").replace('/', "~1");
+            attr_stat.path = format!("{prefix}/{escaped_name}");
+            let attr_path = attr_stat.path.clone();
+            attr_stat
+                .values
+                .iter_mut()
+                .for_each(|value_stat| enrich_with_paths(value_stat, &attr_path));
+        });
+    }
+}
+
+const ELLIPSIS: char = '…';
+
+// the attribute names longer than max_len characters are cut at every level so that they end
+// with … and are max_len characters long, for display only as two names may then be the same
+pub fn truncate_attribute_names(json_stat: &mut JsonStat, max_len: usize) {
+    visit_nested_array_stat_mut(json_stat, |nested_stat| {
+        truncate_attribute_names(nested_stat, max_len)
+    });
+    if let Some(attributes) = json_stat_attributes_mut(json_stat) {
+        attributes.iter_mut().for_each(|attr_stat| {
+            if attr_stat.name.chars().count() > max_len {
+                attr_stat.name = attr_stat
+                    .name
+                    .chars()
+                    .take(max_len.saturating_sub(1))
+                    .chain(std::iter::once(ELLIPSIS))
+                    .take(max_len)
+                    .collect();
+            }
+            attr_stat
+                .values
+                .iter_mut()
+                .for_each(|value_stat| truncate_attribute_names(value_stat, max_len))
+        });
+    }
+}
+
+const HASHED_NAME_BYTES: usize = 4;
+
+// every attribute name is replaced by the first 8 hex digits of its SHA-256, the same name
+// giving the same hash wherever it is in the document
+pub fn hash_attribute_names(json_stat: &mut JsonStat) {
+    visit_nested_array_stat_mut(json_stat, hash_attribute_names);
+    if let Some(attributes) = json_stat_attributes_mut(json_stat) {
+        attributes.iter_mut().for_each(|attr_stat| {
+            attr_stat.name = hash_name(&attr_stat.name);
+            attr_stat.values.iter_mut().for_each(hash_attribute_names)
+        });
+    }
+}
+
+fn hash_name(name: &str) -> String {
+    let digest = Sha256::digest(name.as_bytes());
+    return digest[..HASHED_NAME_BYTES]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+}
+
+// the operations are applied in order to every stat found at their path, a path matching no
+// stat leaves the stat unchanged
+pub fn apply_patch(mut json_stat: JsonStat, patch: JsonStatPatch) -> JsonStat {
+    patch.operations.iter().for_each(|operation| {
+        let path = match operation {
+            JsonStatPatchOperation::SetSize { path, .. }
+            | JsonStatPatchOperation::AddAttribute { path, .. }
+            | JsonStatPatchOperation::RemoveAttribute { path, .. }
+            | JsonStatPatchOperation::SetCount { path, .. } => path,
+        };
+        let path_segments: Vec<String> = path
+            .split('/')
+            .skip(1)
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect();
+        apply_patch_operation(&mut json_stat, &path_segments, operation);
+    });
+    return json_stat;
+}
+
+fn apply_patch_operation(
+    json_stat: &mut JsonStat,
+    path_segments: &[String],
+    operation: &JsonStatPatchOperation,
+) {
+    if let Some((name, remaining_segments)) = path_segments.split_first() {
+        visit_nested_array_stat_mut(json_stat, |nested_stat| {
+            apply_patch_operation(nested_stat, path_segments, operation)
+        });
+        if let Some(attributes) = json_stat_attributes_mut(json_stat) {
+            attributes
+                .iter_mut()
+                .filter(|attr_stat| &attr_stat.name == name)
+                .flat_map(|attr_stat| attr_stat.values.iter_mut())
+                .for_each(|value_stat| {
+                    apply_patch_operation(value_stat, remaining_segments, operation)
+                });
+        }
+        return;
+    }
+    match operation {
+        JsonStatPatchOperation::SetSize { size, .. } => match json_stat {
+            ValStat(vs) => vs.size = *size,
+            ObjStat(vs) => vs.size = *size,
+            ArrayStat(vs) => vs.size = *size,
+        },
+        JsonStatPatchOperation::SetCount { count, .. } => match json_stat {
+            ValStat(_) => {}
+            ObjStat(vs) => vs.count = *count,
+            ArrayStat(vs) => vs.count = *count,
+        },
+        JsonStatPatchOperation::AddAttribute { attr, .. } => {
+            if let Some(attributes) = json_stat_attributes_mut(json_stat) {
+                attributes.push(*attr.clone());
+            }
+        }
+        JsonStatPatchOperation::RemoveAttribute { name, .. } => {
+            if let Some(attributes) = json_stat_attributes_mut(json_stat) {
+                attributes.retain(|attr_stat| &attr_stat.name != name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use std::collections::HashSet;
+
+    use serde_json::json;
+
+    use crate::json_stat_extractor::JsonStat::ArrayStat;
+    use crate::json_stat_extractor::{
+        collect_attribute_names, extract_stat_from_json_value, extract_stat_from_json_with_options,
+        json_stat_attributes, json_stat_nested_array, json_stat_size, JsonStat, JsonStatOptions,
+    };
+    use crate::json_stat_flattener::collect_attribute_paths;
+    use crate::json_stat_transformer::{
+        apply_patch, enrich_with_paths, filter_attributes_by_min_bytes, hash_attribute_names,
+        normalize_stat, omit_attribute, omit_empty_attributes, rename_attribute,
+        truncate_attribute_names, JsonStatPatch, JsonStatPatchOperation,
+    };
+
+    #[test]
+    fn it_should_remove_attributes_below_min_bytes() {
+        let mut json_stat =
+            extract_stat_from_json_value(json!({"large": "0123456789012345678", "tiny": 1}));
+        let size_before_filter = json_stat_size(&json_stat);
+        filter_attributes_by_min_bytes(&mut json_stat, 10);
+        assert_eq!(collect_attribute_names(&json_stat), vec!["large"]);
+        assert_eq!(json_stat_size(&json_stat), size_before_filter);
+    }
+
+    #[test]
+    fn it_should_remove_nested_attributes_below_min_bytes() {
+        let mut json_stat = extract_stat_from_json_value(json!([
+            {"a": {"large": "0123456789", "tiny": 1}},
+            {"a": {"large": "0123456789", "tiny": 2}}
+        ]));
+        filter_attributes_by_min_bytes(&mut json_stat, 20);
+        assert_eq!(collect_attribute_paths(&json_stat), vec!["a"]);
+        let mut json_stat = extract_stat_from_json_value(json!([{"a": {"b": "0123456789"}}]));
+        filter_attributes_by_min_bytes(&mut json_stat, 10);
+        assert_eq!(collect_attribute_paths(&json_stat), vec!["a", "a.b"]);
+    }
+
+    #[test]
+    fn it_should_remove_attributes_with_empty_values_only() {
+        let mut json_stat = extract_stat_from_json_value(json!({
+            "label": "",
+            "tags": [],
+            "extra": {},
+            "code": 12,
+            "items": [{"note": "", "id": 1}, {"note": "", "id": 2}],
+            "comments": ["", "first"]
+        }));
+        omit_empty_attributes(&mut json_stat);
+        assert_eq!(
+            collect_attribute_paths(&json_stat),
+            vec!["code", "comments", "items", "items[].id"]
+        );
+    }
+
+    #[test]
+    fn it_should_make_stats_of_differently_ordered_keys_equal() {
+        let mut json_stat =
+            extract_stat_from_json_value(json!({"b": 1, "a": {"d": [{"f": 1, "e": 2}], "c": 3}}));
+        let mut other_json_stat =
+            extract_stat_from_json_value(json!({"a": {"c": 3, "d": [{"e": 2, "f": 1}]}, "b": 1}));
+        normalize_stat(&mut json_stat);
+        normalize_stat(&mut other_json_stat);
+        assert_eq!(collect_attribute_names(&json_stat), vec!["a", "b"]);
+        assert_eq!(json_stat, other_json_stat);
+    }
+
+    #[test]
+    fn it_should_rename_attributes_at_every_level() {
+        let mut json_stat = extract_stat_from_json_value(json!({
+            "http_response_code": 200,
+            "calls": [{"http_response_code": 404, "path": "/"}]
+        }));
+        rename_attribute(&mut json_stat, "http_response_code", "status_code");
+        assert_eq!(
+            collect_attribute_paths(&json_stat),
+            vec![
+                "calls",
+                "calls[].path",
+                "calls[].status_code",
+                "status_code"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_omit_attributes_at_every_level() {
+        let mut json_stat = extract_stat_from_json_value(json!({
+            "debug": {"trace": "x"},
+            "items": [{"debug": 1, "id": 2}],
+            "name": "a"
+        }));
+        let size_before_omission = json_stat_size(&json_stat);
+        omit_attribute(&mut json_stat, "debug");
+        assert_eq!(
+            collect_attribute_paths(&json_stat),
+            vec!["items", "items[].id", "name"]
+        );
+        assert_eq!(json_stat_size(&json_stat), size_before_omission);
+    }
+
+    #[test]
+    fn it_should_hash_the_same_name_to_the_same_hash() {
+        let mut json_stat = extract_stat_from_json_value(json!({
+            "id": 1,
+            "name": "a",
+            "owner": {"id": 2}
+        }));
+        let size_before_hash = json_stat_size(&json_stat);
+        hash_attribute_names(&mut json_stat);
+        let names = collect_attribute_names(&json_stat);
+        assert!(names.contains(&"a5614527"));
+        assert!(collect_attribute_paths(&json_stat).contains(&"4c102969.a5614527".to_string()));
+        assert_eq!(names.iter().collect::<HashSet<_>>().len(), 3);
+        assert_eq!(json_stat_size(&json_stat), size_before_hash);
+    }
+
+    #[test]
+    fn it_should_patch_only_the_targeted_stats() {
+        let json_stat = extract_stat_from_json_value(json!({
+            "user": {"id": 1, "name": "a"},
+            "tags": [{"id": 2}]
+        }));
+        let new_attr_stat =
+            json_stat_attributes(&extract_stat_from_json_value(json!({"age": 3})))[0].clone();
+        let patch = JsonStatPatch {
+            operations: vec![
+                JsonStatPatchOperation::SetSize {
+                    path: "/user",
+                    size: 100,
+                },
+                JsonStatPatchOperation::RemoveAttribute {
+                    path: "/user",
+                    name: "id",
+                },
+                JsonStatPatchOperation::AddAttribute {
+                    path: "/user",
+                    attr: Box::new(new_attr_stat),
+                },
+                JsonStatPatchOperation::SetCount {
+                    path: "/tags",
+                    count: 5,
+                },
+            ],
+        };
+        let size_before_patch = json_stat_size(&json_stat);
+        let json_stat = apply_patch(json_stat, patch);
+        assert_eq!(json_stat_size(&json_stat), size_before_patch);
+        assert_eq!(
+            collect_attribute_paths(&json_stat),
+            vec!["tags", "tags[].id", "user", "user.age", "user.name"]
+        );
+        let attributes = json_stat_attributes(&json_stat);
+        let user_attr_stat = attributes.iter().find(|a| a.name == "user").unwrap();
+        assert_eq!(json_stat_size(&user_attr_stat.values[0]), 100);
+        let tags_attr_stat = attributes.iter().find(|a| a.name == "tags").unwrap();
+        match &tags_attr_stat.values[0] {
+            ArrayStat(array_stat) => {
+                assert_eq!(array_stat.count, 5);
+            }
+            _ => {
+                assert!(false);
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_set_the_json_pointer_of_nested_attributes() {
+        let mut json_stat = extract_stat_from_json_value(json!({
+            "users": [{"address": {"city": "Paris"}}],
+            "a/b": 1
+        }));
+        enrich_with_paths(&mut json_stat, "");
+        let attributes = json_stat_attributes(&json_stat);
+        let users_attr_stat = attributes.iter().find(|a| a.name == "users").unwrap();
+        assert_eq!(users_attr_stat.path, "/users");
+        let escaped_attr_stat = attributes.iter().find(|a| a.name == "a/b").unwrap();
+        assert_eq!(escaped_attr_stat.path, "/a~1b");
+        let address_attr_stat = &json_stat_attributes(&users_attr_stat.values[0])[0];
+        assert_eq!(address_attr_stat.path, "/users/address");
+        let city_attr_stat = &json_stat_attributes(&address_attr_stat.values[0])[0];
+        assert_eq!(city_attr_stat.path, "/users/address/city");
+    }
+
+    #[test]
+    fn it_should_set_the_item_index_in_the_paths_of_indexed_items() {
+        let options = JsonStatOptions {
+            indexed_array_max_index: Some(0),
+            ..JsonStatOptions::default()
+        };
+        let mut json_stat = extract_stat_from_json_with_options(
+            r#"{"users": [{"address": "Paris"}]}"#.as_bytes(),
+            &options,
+        );
+        enrich_with_paths(&mut json_stat, "/root");
+        let users_attr_stat = &json_stat_attributes(&json_stat)[0];
+        match &users_attr_stat.values[0] {
+            ArrayStat(array_stat) => {
+                let item_attributes = json_stat_attributes(&array_stat.indexed_items[0]);
+                assert_eq!(item_attributes[0].path, "/root/users/0/address");
+            }
+            _ => {
+                assert!(false);
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_truncate_the_long_names_at_every_level() {
+        let long_name = format!("some_long_{}", "x".repeat(40));
+        let mut json_stat = extract_stat_from_json_value(json!({
+            "short": 1,
+            "items": [{long_name.clone(): "a", "ten_chars_": 2}],
+            "été_déjà_vu_là": 3
+        }));
+        truncate_attribute_names(&mut json_stat, 10);
+        let mut paths = collect_attribute_paths(&json_stat);
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "items",
+                "items[].some_long…",
+                "items[].ten_chars_",
+                "short",
+                "été_déjà_…"
+            ]
+        );
+    }
+
+    fn nested_array_stat() -> JsonStat {
+        return extract_stat_from_json_value(json!([
+            [{"secret_name": 1.123456789, "empty": ""}],
+            [{"secret_name": 2, "empty": ""}]
+        ]));
+    }
+
+    fn nested_attribute_names(json_stat: &JsonStat) -> Vec<&str> {
+        return json_stat_nested_array(json_stat)
+            .unwrap()
+            .attributes
+            .iter()
+            .map(|attr_stat| attr_stat.name.as_str())
+            .collect();
+    }
+
+    #[test]
+    fn it_should_filter_the_attributes_of_nested_arrays_by_min_bytes() {
+        let mut json_stat = nested_array_stat();
+        filter_attributes_by_min_bytes(&mut json_stat, 5);
+        assert_eq!(nested_attribute_names(&json_stat), vec!["secret_name"]);
+    }
+
+    #[test]
+    fn it_should_rename_the_attributes_of_nested_arrays() {
+        let mut json_stat = nested_array_stat();
+        rename_attribute(&mut json_stat, "secret_name", "name");
+        let names = nested_attribute_names(&json_stat);
+        assert!(names.contains(&"name"));
+        assert!(!names.contains(&"secret_name"));
+    }
+
+    #[test]
+    fn it_should_omit_the_attributes_of_nested_arrays() {
+        let mut json_stat = nested_array_stat();
+        omit_attribute(&mut json_stat, "secret_name");
+        assert_eq!(nested_attribute_names(&json_stat), vec!["empty"]);
+    }
+
+    #[test]
+    fn it_should_omit_the_empty_attributes_of_nested_arrays() {
+        let mut json_stat = nested_array_stat();
+        omit_empty_attributes(&mut json_stat);
+        assert_eq!(nested_attribute_names(&json_stat), vec!["secret_name"]);
+    }
+
+    #[test]
+    fn it_should_sort_the_attributes_of_nested_arrays() {
+        let mut json_stat = extract_stat_from_json_value(json!([[{"b": 1, "a": 2}]]));
+        normalize_stat(&mut json_stat);
+        assert_eq!(nested_attribute_names(&json_stat), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn it_should_give_their_paths_to_the_attributes_of_nested_arrays() {
+        let mut json_stat = nested_array_stat();
+        enrich_with_paths(&mut json_stat, "");
+        let nested_paths: HashSet<&str> = json_stat_nested_array(&json_stat)
+            .unwrap()
+            .attributes
+            .iter()
+            .map(|attr_stat| attr_stat.path.as_str())
+            .collect();
+        assert_eq!(nested_paths, HashSet::from(["/secret_name", "/empty"]));
+    }
+
+    #[test]
+    fn it_should_truncate_the_attribute_names_of_nested_arrays() {
+        let mut json_stat = nested_array_stat();
+        truncate_attribute_names(&mut json_stat, 6);
+        let names = nested_attribute_names(&json_stat);
+        assert!(names.contains(&"secre…"));
+        assert!(names.contains(&"empty"));
+    }
+
+    #[test]
+    fn it_should_hash_the_attribute_names_of_nested_arrays() {
+        let mut json_stat = nested_array_stat();
+        hash_attribute_names(&mut json_stat);
+        let names = nested_attribute_names(&json_stat);
+        assert!(!names.contains(&"secret_name"));
+        assert!(names.iter().all(|name| name.len() == 8));
+    }
+}
@@ -0,0 +1,357 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::json_stat_extractor::JsonStat::{ArrayStat, ObjStat, ValStat};
+use crate::json_stat_extractor::{
+    extract_stat_from_json_value, json_stat_attributes, JsonAttrStat, JsonStat,
+};
+
+const SIZE_OF_BRACKETS: usize = 2;
+
+// the keys of the item at_index of the root array which differ from the expected keys
+#[derive(Debug, PartialEq, Clone)]
+pub struct SchemaViolation {
+    /// Expected keys the item does not have, in the order of the expected keys.
+    pub missing_keys: Vec<String>,
+    /// Keys of the item which are not expected, in the order of the item.
+    pub extra_keys: Vec<String>,
+    /// Index of the item in the root array, 0 for a root object.
+    pub at_index: usize,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "item {}:", self.at_index)?;
+        if !self.missing_keys.is_empty() {
+            write!(f, " missing keys {}", self.missing_keys.join(", "))?;
+        }
+        if !self.missing_keys.is_empty() && !self.extra_keys.is_empty() {
+            write!(f, ",")?;
+        }
+        if !self.extra_keys.is_empty() {
+            write!(f, " unexpected keys {}", self.extra_keys.join(", "))?;
+        }
+        return Ok(());
+    }
+}
+
+impl std::error::Error for SchemaViolation {}
+
+// every object of a root array, or a root object as item 0, must have exactly the expected
+// keys, the first item which does not gives the violation
+pub fn extract_stat_with_schema_validation(
+    value: &Value,
+    expected_keys: &[&str],
+) -> Result<JsonStat, SchemaViolation> {
+    let json_stat = extract_stat_from_json_value(value.clone());
+    let items = match value {
+        Value::Array(items) => items.as_slice(),
+        _ => std::slice::from_ref(value),
+    };
+    for (index, item) in items.iter().enumerate() {
+        let item_keys: Vec<&str> = match item {
+            Value::Object(attributes) => attributes.keys().map(String::as_str).collect(),
+            _ => vec![],
+        };
+        let missing_keys: Vec<String> = expected_keys
+            .iter()
+            .filter(|expected_key| !item_keys.contains(expected_key))
+            .map(|expected_key| expected_key.to_string())
+            .collect();
+        let extra_keys: Vec<String> = item_keys
+            .iter()
+            .filter(|item_key| !expected_keys.contains(item_key))
+            .map(|item_key| item_key.to_string())
+            .collect();
+        if !missing_keys.is_empty() || !extra_keys.is_empty() {
+            return Err(SchemaViolation {
+                missing_keys,
+                extra_keys,
+                at_index: index,
+            });
+        }
+    }
+    return Ok(json_stat);
+}
+
+// checks that min_size <= size <= max_size and that counts are consistent, for the whole tree.
+// The size of an array being its whole serialized size, it is checked against the sizes of its
+// items, the same goes for the attributes whose values are arrays.
+pub fn validate_stat_invariants(json_stat: &JsonStat) -> Vec<String> {
+    let mut violations: Vec<String> = vec![];
+    collect_stat_violations(json_stat, "", &mut violations);
+    return violations;
+}
+
+// attributes whose values span several JSON types, which usually hints at a data bug
+pub fn collect_mixed_type_warnings(json_stat: &JsonStat) -> Vec<String> {
+    let mut warnings: Vec<String> = vec![];
+    collect_stat_mixed_types(json_stat, "", &mut warnings);
+    return warnings;
+}
+
+fn collect_stat_mixed_types(json_stat: &JsonStat, path: &str, warnings: &mut Vec<String>) {
+    json_stat_attributes(json_stat)
+        .iter()
+        .for_each(|attr_stat| {
+            let attr_path = format!("{path}/{}", attr_stat.name);
+            if attr_stat.type_counts.len() > 1 {
+                let type_counts: Vec<String> = attr_stat
+                    .type_counts
+                    .iter()
+                    .map(|(type_name, count)| format!("{type_name} {count}"))
+                    .collect();
+                warnings.push(format!(
+                    "{attr_path}: mixed types {}",
+                    type_counts.join(", ")
+                ));
+            }
+            attr_stat
+                .values
+                .iter()
+                .for_each(|value_stat| collect_stat_mixed_types(value_stat, &attr_path, warnings));
+        });
+}
+
+fn collect_stat_violations(json_stat: &JsonStat, path: &str, violations: &mut Vec<String>) {
+    let location = if path.is_empty() { "root" } else { path };
+    let attributes = match json_stat {
+        ValStat(vs) => {
+            check_size_range(location, vs.size, vs.min_size, vs.max_size, violations);
+            return;
+        }
+        ObjStat(vs) => {
+            check_size_range(location, vs.size, vs.min_size, vs.max_size, violations);
+            if vs.count < 1 {
+                violations.push(format!("{location}: object count is 0"));
+            }
+            &vs.attributes
+        }
+        ArrayStat(vs) => {
+            check_array_sizes(
+                location,
+                vs.size,
+                vs.count,
+                vs.min_size,
+                vs.max_size,
+                violations,
+            );
+            if vs.count < 1 && !vs.attributes.is_empty() {
+                violations.push(format!(
+                    "{location}: array count is 0 but it has {} attributes",
+                    vs.attributes.len()
+                ));
+            }
+            &vs.attributes
+        }
+    };
+    attributes
+        .iter()
+        .for_each(|attr_stat| collect_attr_stat_violations(attr_stat, path, violations));
+}
+
+fn collect_attr_stat_violations(
+    attr_stat: &JsonAttrStat,
+    path: &str,
+    violations: &mut Vec<String>,
+) {
+    let attr_path = format!("{path}/{}", attr_stat.name);
+    if attr_stat.count < 1 {
+        violations.push(format!("{attr_path}: attribute count is 0"));
+    }
+    let has_array_values = attr_stat
+        .values
+        .iter()
+        .any(|value_stat| matches!(value_stat, ArrayStat(_)));
+    if has_array_values {
+        check_min_max(
+            &attr_path,
+            attr_stat.min_size,
+            attr_stat.max_size,
+            violations,
+        );
+    } else {
+        check_size_range(
+            &attr_path,
+            attr_stat.size,
+            attr_stat.min_size,
+            attr_stat.max_size,
+            violations,
+        );
+    }
+    attr_stat
+        .values
+        .iter()
+        .for_each(|value_stat| collect_stat_violations(value_stat, &attr_path, violations));
+}
+
+fn check_min_max(location: &str, min_size: usize, max_size: usize, violations: &mut Vec<String>) {
+    if min_size > max_size {
+        violations.push(format!(
+            "{location}: min_size {min_size} is greater than max_size {max_size}"
+        ));
+    }
+}
+
+fn check_size_range(
+    location: &str,
+    size: usize,
+    min_size: usize,
+    max_size: usize,
+    violations: &mut Vec<String>,
+) {
+    check_min_max(location, min_size, max_size, violations);
+    if size < min_size || size > max_size {
+        violations.push(format!(
+            "{location}: size {size} is out of [{min_size}, {max_size}]"
+        ));
+    }
+}
+
+fn check_array_sizes(
+    location: &str,
+    size: usize,
+    count: usize,
+    min_size: usize,
+    max_size: usize,
+    violations: &mut Vec<String>,
+) {
+    check_min_max(location, min_size, max_size, violations);
+    if count == 0 {
+        return;
+    }
+    let size_of_commas = count - 1;
+    let smallest_size = min_size * count + size_of_commas + SIZE_OF_BRACKETS;
+    let largest_size = max_size * count + size_of_commas + SIZE_OF_BRACKETS;
+    if size < smallest_size || size > largest_size {
+        violations.push(format!(
+            "{location}: size {size} of {count} items is out of [{smallest_size}, {largest_size}]"
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::json_stat_extractor::JsonStat::ObjStat;
+    use crate::json_stat_extractor::{extract_stat_from_json_value, merge_stats};
+    use crate::json_stat_validator::{
+        collect_mixed_type_warnings, extract_stat_with_schema_validation, validate_stat_invariants,
+        SchemaViolation,
+    };
+
+    #[test]
+    fn it_should_validate_stats_produced_by_the_extractor() {
+        let documents = vec![
+            json!("test"),
+            json!(null),
+            json!(true),
+            json!(12.5),
+            json!({"test":"test"}),
+            json!(["test", "test0123456789"]),
+            json!([{"test":"test"}, {"test":"test3", "b": true}]),
+            json!({"a":{"b": "0123456789"}}),
+            json!([{"a":{"b": "0123456789"}}, {"a":{"b": "0123456789"}}, {"a":{"c": "ABC"}}]),
+            json!({"tags":["a","bbbb"], "nested": [[1, 22], [333]]}),
+            json!([]),
+            json!({"a": [], "b": [[], {}]}),
+            json!([{"tags":["a","bbbb"]}, {"tags":"cc"}, 1, [2]]),
+        ];
+        documents.into_iter().for_each(|document| {
+            let json_stat = extract_stat_from_json_value(document.clone());
+            assert_eq!(
+                validate_stat_invariants(&json_stat),
+                Vec::<String>::new(),
+                "{document}"
+            );
+        });
+    }
+
+    #[test]
+    fn it_should_validate_merged_stats() {
+        let json_stat = merge_stats(
+            extract_stat_from_json_value(json!([{"a":1}, {"a":"test"}])),
+            extract_stat_from_json_value(json!([{"a":{"b":2}}, {"c":null}])),
+        );
+        assert!(validate_stat_invariants(&json_stat).is_empty());
+    }
+
+    #[test]
+    fn it_should_report_violations_with_their_location() {
+        let mut json_stat = extract_stat_from_json_value(json!({"a": {"b": "test"}}));
+        if let ObjStat(obj_stat) = &mut json_stat {
+            obj_stat.min_size = 100;
+            let attr_stat = obj_stat.attributes.first_mut().unwrap();
+            attr_stat.count = 0;
+            if let Some(ObjStat(nested_obj_stat)) = attr_stat.values.first_mut() {
+                nested_obj_stat.attributes.first_mut().unwrap().max_size = 1;
+            }
+        }
+        let violations = validate_stat_invariants(&json_stat);
+        assert_eq!(
+            violations,
+            vec![
+                "root: min_size 100 is greater than max_size 18",
+                "root: size 18 is out of [100, 18]",
+                "/a: attribute count is 0",
+                "/a/b: min_size 6 is greater than max_size 1",
+                "/a/b: size 6 is out of [6, 1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_warn_on_mixed_type_attributes() {
+        let json_stat = extract_stat_from_json_value(json!([{"x": 1}, {"x": "a"}]));
+        assert_eq!(
+            collect_mixed_type_warnings(&json_stat),
+            vec!["/x: mixed types number 1, string 1"]
+        );
+        let json_stat =
+            extract_stat_from_json_value(json!({"o": [{"y": null}, {"y": true}], "z": 1}));
+        assert_eq!(
+            collect_mixed_type_warnings(&json_stat),
+            vec!["/o/y: mixed types boolean 1, null 1"]
+        );
+    }
+
+    #[test]
+    fn it_should_extract_stat_matching_the_schema() {
+        let value = json!([{"id": 1, "name": "a"}, {"name": "b", "id": 2}]);
+        let result = extract_stat_with_schema_validation(&value, &["id", "name"]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_should_report_the_first_schema_violation() {
+        let value = json!([{"id": 1, "name": "a"}, {"id": 2, "nick": "b"}, {"id": 3}]);
+        let result = extract_stat_with_schema_validation(&value, &["id", "name"]);
+        assert_eq!(
+            result.err(),
+            Some(SchemaViolation {
+                missing_keys: vec!["name".to_string()],
+                extra_keys: vec!["nick".to_string()],
+                at_index: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_describe_a_schema_violation() {
+        let value = json!([{"id": 1, "nick": "b"}]);
+        let violation = extract_stat_with_schema_validation(&value, &["id", "name"])
+            .err()
+            .unwrap();
+        assert_eq!(violation.at_index, 0);
+        assert_eq!(
+            violation.to_string(),
+            "item 0: missing keys name, unexpected keys nick"
+        );
+        let violation = extract_stat_with_schema_validation(&json!({"id": 1}), &["id", "name"])
+            .err()
+            .unwrap();
+        assert_eq!(violation.to_string(), "item 0: missing keys name");
+    }
+}
@@ -0,0 +1,14 @@
+pub mod exporters;
+pub mod json_stat_cache;
+pub mod json_stat_compact;
+pub mod json_stat_diff;
+pub mod json_stat_error;
+pub mod json_stat_extractor;
+pub mod json_stat_flattener;
+pub mod json_stat_jq;
+pub mod json_stat_sampler;
+pub mod json_stat_schema;
+pub mod json_stat_transformer;
+pub mod json_stat_validator;
+pub mod limited_reader;
+pub mod stats_math;
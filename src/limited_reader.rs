@@ -0,0 +1,68 @@
+use std::io::{Read, Result};
+
+// reads at most a given number of bytes of the inner reader, like `std::io::Take`, the JSON
+// parser then sees the end of the input at the limit
+pub struct LimitedReader<R: Read> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: Read> LimitedReader<R> {
+    pub fn new(inner: R, limit: u64) -> Self {
+        LimitedReader {
+            inner,
+            remaining: limit,
+        }
+    }
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if self.remaining == 0 {
+            return Ok(0);
+        }
+        let max_read_size = (buf.len() as u64).min(self.remaining) as usize;
+        let read_size = self.inner.read(&mut buf[..max_read_size])?;
+        self.remaining -= read_size as u64;
+        return Ok(read_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use crate::json_stat_error::JsonStatError;
+    use crate::json_stat_extractor::{try_extract_stat_from_json_with_options, JsonStatOptions};
+    use crate::limited_reader::LimitedReader;
+
+    #[test]
+    fn it_should_read_up_to_the_limit() {
+        let mut content = String::new();
+        LimitedReader::new("abcdef".as_bytes(), 3)
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "abc");
+        let mut content = String::new();
+        LimitedReader::new("abcdef".as_bytes(), 10)
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "abcdef");
+    }
+
+    #[test]
+    fn it_should_report_a_document_truncated_by_the_limit_as_partial_input() {
+        let json_content = r#"{"a":1,"b":"test"}"#;
+        let options = JsonStatOptions::default();
+        let result = try_extract_stat_from_json_with_options(
+            LimitedReader::new(json_content.as_bytes(), 10),
+            &options,
+        );
+        assert!(matches!(result, Err(JsonStatError::PartialInput(_))));
+        let result = try_extract_stat_from_json_with_options(
+            LimitedReader::new(json_content.as_bytes(), 100),
+            &options,
+        );
+        assert!(result.is_ok());
+    }
+}
@@ -1,22 +1,1167 @@
-use crate::json_stat_extractor::{extract_stat_from_json, JsonStat};
+use flate2::read::MultiGzDecoder;
+use itertools::Itertools;
+use jsonstat::exporters::avro::json_stat_to_schema_registry_avro;
+use jsonstat::exporters::bar_chart::json_stat_to_clipped_ascii_bar_chart;
+use jsonstat::exporters::csv::json_stat_to_heatmap_csv;
+use jsonstat::exporters::dot::json_stat_to_dot;
+use jsonstat::exporters::openapi::json_stat_to_openapi_schema_component;
+#[cfg(feature = "parquet")]
+use jsonstat::exporters::parquet::json_stat_to_parquet;
+use jsonstat::exporters::summary::json_stat_to_summary_string;
+use jsonstat::exporters::toml::json_stat_to_toml;
+use jsonstat::exporters::yaml::stats_to_yaml;
+use jsonstat::json_stat_diff::diff_json_stats;
+use jsonstat::json_stat_error::JsonStatError;
+use jsonstat::json_stat_extractor::{
+    collect_attribute_names, extract_attribute_stats_only, extract_stat_from_value_with_options,
+    extract_stats_grouped_by, merge_stats, round_json_stat,
+    try_extract_document_stats_from_ndjson_with_options,
+    try_extract_item_stats_from_json_with_options,
+    try_extract_stat_and_summary_from_json_stream_with_options,
+    try_extract_stat_and_summary_from_json_with_options,
+    try_extract_stat_and_summary_from_ndjson_with_options,
+    try_extract_stat_from_json_as_array_with_options,
+    try_extract_stat_from_json_stream_with_options, try_extract_stat_from_json_with_options,
+    try_extract_stat_from_jsonc_with_options, try_extract_stat_from_ndjson_with_options,
+    try_read_json_value, JsonStat, JsonStatOptions,
+};
+use jsonstat::json_stat_flattener::{collect_attribute_paths, flatten_json_stat};
+use jsonstat::json_stat_jq::apply_jq_filter;
+use jsonstat::json_stat_sampler::sample_items;
+use jsonstat::json_stat_transformer::{
+    enrich_with_paths, filter_attributes_by_min_bytes, hash_attribute_names, normalize_stat,
+    omit_attribute, omit_empty_attributes, rename_attribute,
+};
+use jsonstat::json_stat_validator::{collect_mixed_type_warnings, validate_stat_invariants};
+use jsonstat::limited_reader::LimitedReader;
+use jsonstat::stats_math::{attribute_value_sizes, size_cdf};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::env;
 use std::env::args;
+use std::fs;
 use std::fs::File;
-use std::io::{stdin, BufReader};
+use std::io::{stdin, stdout, BufReader, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use terminal_size::{terminal_size, Width};
 
-mod json_stat_extractor;
+use crate::cli::{parse_cli_options, CliOptions, ColorChoice, OutputFormat};
+
+mod cli;
+
+const DEFAULT_COMPONENT_NAME: &str = "Document";
+const DEFAULT_REPORT_NAME: &str = "report";
+const BAR_CHART_WIDTH: usize = 40;
+// line width of the bar chart when the output is not a terminal and COLUMNS is not set
+const DEFAULT_LINE_WIDTH: usize = 80;
 
 fn main() {
-    let mut args = args();
-    let args_length = args.len();
-    let json_stat: JsonStat = if args_length > 1 {
-        let file_name = args.nth(1).unwrap();
-        println!("will parse {file_name}");
-        let file = File::open(file_name).unwrap();
-        let file_reader = BufReader::new(file);
-        extract_stat_from_json(file_reader)
+    let cli_options = parse_cli_options(args()).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        exit(2);
+    });
+    if let Some(output_dir) = &cli_options.output_dir {
+        write_reports(output_dir, &cli_options).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            exit(2);
+        });
+    }
+    if let Some(heatmap_output) = &cli_options.heatmap_output {
+        write_heatmap(heatmap_output, &cli_options).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            exit(2);
+        });
+    }
+    if cli_options.output_dir.is_some() || cli_options.heatmap_output.is_some() {
+        return;
+    }
+    if let Some(sample_files) = cli_options.sample_files {
+        let json_stat =
+            extract_sampled_files_stat(sample_files, &cli_options).unwrap_or_else(|error| {
+                eprintln!("{error}");
+                exit(2);
+            });
+        print!("{}", render_output(json_stat, &cli_options));
+        return;
+    }
+    let json_content_reader: Box<dyn Read> = if let Some(file_name) = &cli_options.file_name {
+        eprintln!("will parse {file_name}");
+        let file = File::open(file_name)
+            .map_err(|error| format!("cannot open {file_name}: {error}"))
+            .unwrap_or_else(|error| {
+                eprintln!("{error}");
+                exit(2);
+            });
+        Box::new(BufReader::new(file))
     } else {
-        extract_stat_from_json(stdin())
+        Box::new(stdin())
     };
-    let json_stat_in_json = serde_json::to_string_pretty(&json_stat).unwrap();
-    println!("{json_stat_in_json}")
+    let json_content_reader = decode_reader(json_content_reader, &cli_options);
+    if cli_options.document_summary {
+        let (_, document_summary) = if cli_options.ndjson {
+            try_extract_stat_and_summary_from_ndjson_with_options(
+                BufReader::new(json_content_reader),
+                &json_stat_options(&cli_options),
+            )
+        } else if cli_options.concatenated {
+            try_extract_stat_and_summary_from_json_stream_with_options(
+                json_content_reader,
+                &json_stat_options(&cli_options),
+            )
+            .map(|(json_stat, document_summary, skipped_document_count)| {
+                if skipped_document_count > 0 {
+                    eprintln!("skipped {skipped_document_count} malformed documents");
+                }
+                (json_stat, document_summary)
+            })
+        } else {
+            try_extract_stat_and_summary_from_json_with_options(
+                json_content_reader,
+                &json_stat_options(&cli_options),
+            )
+        }
+        .unwrap_or_else(|error| {
+            eprintln!("{error}");
+            exit(2);
+        });
+        print!("{}", to_pretty_json(&document_summary));
+        return;
+    }
+    if cli_options.no_aggregate {
+        let item_stats = try_extract_item_stats_from_json_with_options(
+            json_content_reader,
+            &json_stat_options(&cli_options),
+        )
+        .unwrap_or_else(|error| {
+            eprintln!("{error}");
+            exit(2);
+        });
+        print!("{}", render_item_stats(item_stats, &cli_options));
+        return;
+    }
+    if cli_options.per_document {
+        let options = json_stat_options(&cli_options);
+        let document_stats = try_extract_document_stats_from_ndjson_with_options(
+            BufReader::new(json_content_reader),
+            &options,
+        );
+        write_document_stats(document_stats, &cli_options, &mut stdout().lock()).unwrap_or_else(
+            |error| {
+                eprintln!("{error}");
+                exit(2);
+            },
+        );
+        return;
+    }
+    if let Some(cdf_path) = &cli_options.cdf {
+        let json_value = try_read_json_value(json_content_reader).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            exit(2);
+        });
+        let sizes = attribute_value_sizes(&json_value, cdf_path);
+        print!(
+            "{}",
+            to_pretty_json(&size_cdf(&sizes, cli_options.cdf_points))
+        );
+        return;
+    }
+    if let Some(key) = &cli_options.group_by {
+        let json_value = try_read_json_value(json_content_reader).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            exit(2);
+        });
+        print!("{}", render_groups(json_value, key, &cli_options));
+        return;
+    }
+    let json_stat: JsonStat =
+        extract_json_stat(json_content_reader, &cli_options).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            exit(2);
+        });
+    if cli_options.debug {
+        validate_stat_invariants(&json_stat)
+            .iter()
+            .for_each(|violation| eprintln!("invariant violation {violation}"));
+    }
+    if cli_options.warn_mixed_types {
+        collect_mixed_type_warnings(&json_stat)
+            .iter()
+            .for_each(|warning| eprintln!("warning {warning}"));
+    }
+    if let Some(baseline) = &cli_options.baseline {
+        let baseline_stat = read_baseline_stat(baseline).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            exit(2);
+        });
+        print!("{}", render_delta(baseline_stat, json_stat, &cli_options));
+        return;
+    }
+    #[cfg(feature = "parquet")]
+    if let (OutputFormat::Parquet, Some(output)) = (&cli_options.format, &cli_options.output) {
+        write_parquet(json_stat, output, &cli_options).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            exit(2);
+        });
+        return;
+    }
+    print!("{}", render_output(json_stat, &cli_options));
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(
+    mut json_stat: JsonStat,
+    output: &str,
+    cli_options: &CliOptions,
+) -> Result<(), String> {
+    transform_stat(&mut json_stat, cli_options);
+    let file = File::create(output).map_err(|error| format!("cannot create {output}: {error}"))?;
+    return json_stat_to_parquet(&json_stat, file)
+        .map_err(|error| format!("cannot write {output}: {error}"));
+}
+
+// the content is decompressed then limited
+fn decode_reader(json_content_reader: Box<dyn Read>, cli_options: &CliOptions) -> Box<dyn Read> {
+    let json_content_reader: Box<dyn Read> = if cli_options.gzip {
+        Box::new(MultiGzDecoder::new(json_content_reader))
+    } else {
+        json_content_reader
+    };
+    return if let Some(limit) = cli_options.limit {
+        Box::new(LimitedReader::new(json_content_reader, limit))
+    } else {
+        json_content_reader
+    };
+}
+
+// one report per input file written to <output_dir>/<file stem>.stats.json, the directory being
+// created when missing, a number being added to the stem of input files having the same stem,
+// like users-2.stats.json
+fn write_reports(output_dir: &str, cli_options: &CliOptions) -> Result<Vec<PathBuf>, String> {
+    fs::create_dir_all(output_dir)
+        .map_err(|error| format!("cannot create {output_dir}: {error}"))?;
+    let mut report_names: HashSet<String> = HashSet::new();
+    return cli_options
+        .file_names
+        .iter()
+        .map(|file_name| {
+            let json_stat = extract_file_stat(file_name, cli_options)?;
+            let file_stem = Path::new(file_name)
+                .file_stem()
+                .and_then(|file_stem| file_stem.to_str())
+                .unwrap_or(DEFAULT_REPORT_NAME);
+            let report_name = (1..)
+                .map(|index| match index {
+                    1 => format!("{file_stem}.stats.json"),
+                    _ => format!("{file_stem}-{index}.stats.json"),
+                })
+                .find(|report_name| !report_names.contains(report_name))
+                .unwrap_or_default();
+            report_names.insert(report_name.clone());
+            let report_path = Path::new(output_dir).join(report_name);
+            fs::write(&report_path, render_output(json_stat, cli_options))
+                .map_err(|error| format!("cannot write {}: {error}", report_path.display()))?;
+            eprintln!("wrote {}", report_path.display());
+            return Ok(report_path);
+        })
+        .collect();
+}
+
+// the average attribute sizes of every input file, one row per file labeled with its name
+fn write_heatmap(heatmap_output: &str, cli_options: &CliOptions) -> Result<(), String> {
+    let stats = cli_options
+        .file_names
+        .iter()
+        .map(|file_name| {
+            Ok((
+                file_name.clone(),
+                extract_file_stat(file_name, cli_options)?,
+            ))
+        })
+        .collect::<Result<Vec<(String, JsonStat)>, String>>()?;
+    fs::write(heatmap_output, json_stat_to_heatmap_csv(&stats))
+        .map_err(|error| format!("cannot write {heatmap_output}: {error}"))?;
+    eprintln!("wrote {heatmap_output}");
+    return Ok(());
+}
+
+// the stats of sample_files input files picked at random are merged, the directories being read
+// recursively
+fn extract_sampled_files_stat(
+    sample_files: usize,
+    cli_options: &CliOptions,
+) -> Result<JsonStat, String> {
+    let file_names = sample_file_names(sample_files, cli_options)?;
+    return file_names
+        .iter()
+        .map(|file_name| extract_file_stat(file_name, cli_options))
+        .reduce(|json_stat, other_json_stat| Ok(merge_stats(json_stat?, other_json_stat?)))
+        .unwrap_or_else(|| Err("no input file to sample".to_string()));
+}
+
+// the files are listed in name order before the sampling, so that a seed gives the same files
+fn sample_file_names(sample_files: usize, cli_options: &CliOptions) -> Result<Vec<String>, String> {
+    let mut file_names: Vec<String> = vec![];
+    for file_name in &cli_options.file_names {
+        collect_file_names(Path::new(file_name), &mut file_names)?;
+    }
+    file_names.sort_unstable();
+    let file_count = file_names.len();
+    let sampled_file_names = sample_items(file_names, sample_files, cli_options.sample_seed);
+    eprintln!("sampled {} of {file_count} files", sampled_file_names.len());
+    return Ok(sampled_file_names);
+}
+
+fn collect_file_names(path: &Path, file_names: &mut Vec<String>) -> Result<(), String> {
+    if !path.is_dir() {
+        file_names.push(path.to_string_lossy().to_string());
+        return Ok(());
+    }
+    let entries =
+        fs::read_dir(path).map_err(|error| format!("cannot read {}: {error}", path.display()))?;
+    for entry in entries {
+        let entry = entry.map_err(|error| format!("cannot read {}: {error}", path.display()))?;
+        collect_file_names(&entry.path(), file_names)?;
+    }
+    return Ok(());
+}
+
+fn extract_file_stat(file_name: &str, cli_options: &CliOptions) -> Result<JsonStat, String> {
+    let file =
+        File::open(file_name).map_err(|error| format!("cannot open {file_name}: {error}"))?;
+    let json_content_reader = decode_reader(Box::new(BufReader::new(file)), cli_options);
+    return extract_json_stat(json_content_reader, cli_options)
+        .map_err(|error| format!("{file_name}: {error}"));
+}
+
+fn read_baseline_stat(baseline: &str) -> Result<JsonStat, String> {
+    let file = File::open(baseline).map_err(|error| format!("cannot open {baseline}: {error}"))?;
+    return serde_json::from_reader(BufReader::new(file))
+        .map_err(|error| format!("invalid baseline {baseline}: {error}"));
+}
+
+// both stats are transformed alike, so that a baseline saved without --hash-names or
+// --rename-attribute is compared under the same names and the original names are not printed
+fn render_delta(
+    mut baseline_stat: JsonStat,
+    mut json_stat: JsonStat,
+    cli_options: &CliOptions,
+) -> String {
+    transform_stat(&mut baseline_stat, cli_options);
+    transform_stat(&mut json_stat, cli_options);
+    let stat_delta = diff_json_stats(&baseline_stat, &json_stat);
+    return to_pretty_json(&stat_delta);
+}
+
+// the extraction options given on the command line, the same for every input mode
+fn json_stat_options(cli_options: &CliOptions) -> JsonStatOptions {
+    return JsonStatOptions {
+        sample_size: cli_options.sample_size,
+        sample_seed: cli_options.sample_seed,
+        skip_values: cli_options.skip_values,
+        renamed_keys: cli_options.renamed_keys.clone(),
+        indexed_array_max_index: if cli_options.flatten_arrays {
+            Some(cli_options.max_array_index)
+        } else {
+            None
+        },
+        size_unit: cli_options.size_unit.clone(),
+        collapse_singletons: cli_options.collapse_singletons,
+        string_size_cap: cli_options.string_size_cap,
+        pretty_line_ending: cli_options.line_ending.clone(),
+        ..JsonStatOptions::default()
+    };
+}
+
+// every input mode returns its parse or read error, like a partial input cut by --limit
+fn extract_json_stat<R>(
+    json_content_reader: R,
+    cli_options: &CliOptions,
+) -> Result<JsonStat, JsonStatError>
+where
+    R: Read,
+{
+    let options = json_stat_options(cli_options);
+    return if let Some(jq_path) = &cli_options.jq_path {
+        let selected_value = select_jq_path(json_content_reader, jq_path)?;
+        Ok(extract_stat_from_value_with_options(
+            selected_value,
+            &options,
+        ))
+    } else if cli_options.concatenated {
+        let (json_stat, skipped_document_count) =
+            try_extract_stat_from_json_stream_with_options(json_content_reader, &options)?;
+        if skipped_document_count > 0 {
+            eprintln!("skipped {skipped_document_count} malformed documents");
+        }
+        Ok(json_stat)
+    } else if cli_options.ndjson {
+        try_extract_stat_from_ndjson_with_options(BufReader::new(json_content_reader), &options)
+    } else if cli_options.jsonc {
+        try_extract_stat_from_jsonc_with_options(json_content_reader, &options)
+    } else if cli_options.as_array {
+        try_extract_stat_from_json_as_array_with_options(json_content_reader, &options)
+    } else {
+        try_extract_stat_from_json_with_options(json_content_reader, &options)
+    };
+}
+
+// a single jq output is analyzed as is, several outputs are analyzed as the items of an array
+fn select_jq_path<R>(json_content_reader: R, jq_path: &str) -> Result<Value, JsonStatError>
+where
+    R: Read,
+{
+    let json_value = try_read_json_value(json_content_reader)?;
+    let mut selected_values = apply_jq_filter(json_value, jq_path)?;
+    return Ok(if selected_values.len() == 1 {
+        selected_values.remove(0)
+    } else {
+        Value::Array(selected_values)
+    });
+}
+
+// a serialization error is reported and ends the process instead of panicking in the middle of
+// a pipeline
+fn to_pretty_json<T>(value: &T) -> String
+where
+    T: Serialize,
+{
+    return try_to_pretty_json(value).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        exit(2);
+    });
+}
+
+fn try_to_pretty_json<T>(value: &T) -> Result<String, JsonStatError>
+where
+    T: Serialize,
+{
+    return serde_json::to_string_pretty(value)
+        .map(|json_output| json_output + "\n")
+        .map_err(|error| JsonStatError::Serialization(error.to_string()));
+}
+
+// the text outputs are colored on a terminal, JSON stays uncolored whatever the choice
+fn colors_enabled(cli_options: &CliOptions) -> bool {
+    return match cli_options.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => stdout().is_terminal(),
+    };
+}
+
+// the kind in bold and the sizes and counts highlighted
+fn colorize_summary(summary: &str) -> String {
+    let (kind, figures) = summary.split_once(": ").unwrap_or(("", summary));
+    let figures = figures
+        .split(' ')
+        .map(|word| {
+            if word.starts_with(|character: char| character.is_ascii_digit()) {
+                word.yellow().to_string()
+            } else {
+                word.to_string()
+            }
+        })
+        .join(" ");
+    return format!("{}: {}", kind.bold(), figures);
+}
+
+fn render_item_stats(mut item_stats: Vec<JsonStat>, cli_options: &CliOptions) -> String {
+    item_stats
+        .iter_mut()
+        .for_each(|item_stat| round_json_stat(item_stat, cli_options.precision));
+    return to_pretty_json(&item_stats);
+}
+
+// the stat of each group of items by the value of the key
+fn render_groups(json_value: Value, key: &str, cli_options: &CliOptions) -> String {
+    let options = json_stat_options(cli_options);
+    let group_stats: BTreeMap<String, JsonStat> =
+        extract_stats_grouped_by(json_value, key, &options)
+            .into_iter()
+            .map(|(group, array_stat)| {
+                let mut group_stat = JsonStat::ArrayStat(array_stat);
+                transform_stat(&mut group_stat, cli_options);
+                round_json_stat(&mut group_stat, cli_options.precision);
+                (group, group_stat)
+            })
+            .collect();
+    return to_pretty_json(&group_stats);
+}
+
+// one compact JSON stat per line, like the NDJSON input
+// each stat is written as soon as its line is read, the writing stopping at the first error, the
+// stats of the previous lines being already written
+fn write_document_stats<I, W>(
+    document_stats: I,
+    cli_options: &CliOptions,
+    output: &mut W,
+) -> Result<(), JsonStatError>
+where
+    I: Iterator<Item = Result<JsonStat, JsonStatError>>,
+    W: Write,
+{
+    for document_stat in document_stats {
+        let mut document_stat = document_stat?;
+        transform_stat(&mut document_stat, cli_options);
+        round_json_stat(&mut document_stat, cli_options.precision);
+        serde_json::to_writer(&mut *output, &document_stat)
+            .map_err(|error| JsonStatError::Serialization(error.to_string()))?;
+        writeln!(output).map_err(|error| JsonStatError::Io(error.to_string()))?;
+    }
+    return Ok(());
+}
+
+// the attributes filtered, renamed and sorted as asked
+fn transform_stat(json_stat: &mut JsonStat, cli_options: &CliOptions) {
+    if !cli_options.include_empty {
+        omit_empty_attributes(json_stat);
+    }
+    if let Some(min_bytes) = cli_options.min_bytes {
+        filter_attributes_by_min_bytes(json_stat, min_bytes);
+    }
+    cli_options
+        .omitted_attributes
+        .iter()
+        .for_each(|name| omit_attribute(json_stat, name));
+    cli_options
+        .renamed_attributes
+        .iter()
+        .for_each(|(old_name, new_name)| rename_attribute(json_stat, old_name, new_name));
+    if cli_options.hash_names {
+        hash_attribute_names(json_stat);
+    }
+    if cli_options.normalize {
+        normalize_stat(json_stat);
+    }
+    if cli_options.include_path_stats {
+        enrich_with_paths(json_stat, "");
+    }
+}
+
+fn render_output(mut json_stat: JsonStat, cli_options: &CliOptions) -> String {
+    transform_stat(&mut json_stat, cli_options);
+    if cli_options.keys_only {
+        let attribute_names: Vec<String> = if cli_options.flatten {
+            collect_attribute_paths(&json_stat)
+        } else {
+            collect_attribute_names(&json_stat)
+                .into_iter()
+                .map(String::from)
+                .collect()
+        };
+        let colors = colors_enabled(cli_options);
+        return attribute_names
+            .into_iter()
+            .map(|attribute_name| {
+                if colors {
+                    attribute_name.cyan().to_string() + "\n"
+                } else {
+                    attribute_name + "\n"
+                }
+            })
+            .collect();
+    }
+    if cli_options.summarize {
+        let summary = json_stat_to_summary_string(&json_stat);
+        if colors_enabled(cli_options) {
+            return colorize_summary(&summary) + "\n";
+        }
+        return summary + "\n";
+    }
+    round_json_stat(&mut json_stat, cli_options.precision);
+    return match cli_options.format {
+        OutputFormat::Json if cli_options.flatten => {
+            let flat_attribute_stats = flatten_json_stat(&json_stat);
+            to_pretty_json(&flat_attribute_stats)
+        }
+        OutputFormat::Json if cli_options.field_stats_only => {
+            let attribute_stats = extract_attribute_stats_only(json_stat);
+            to_pretty_json(&attribute_stats)
+        }
+        OutputFormat::Json => to_pretty_json(&json_stat),
+        OutputFormat::Dot => json_stat_to_dot(&json_stat),
+        OutputFormat::Toml => json_stat_to_toml(&json_stat).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            exit(2);
+        }),
+        OutputFormat::Yaml => stats_to_yaml(&json_stat).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            exit(2);
+        }),
+        OutputFormat::OpenApiComponent => to_pretty_json(&json_stat_to_openapi_schema_component(
+            &json_stat,
+            &component_name(cli_options),
+        )),
+        // the Parquet file is written by write_parquet, the text outputs (like the reports of
+        // --output-dir) getting the same flattened records as JSON
+        #[cfg(feature = "parquet")]
+        OutputFormat::Parquet => to_pretty_json(&flatten_json_stat(&json_stat)),
+        OutputFormat::ConfluentAvro => to_pretty_json(&json_stat_to_schema_registry_avro(
+            &json_stat,
+            &component_name(cli_options),
+        )),
+        OutputFormat::BarChart => {
+            json_stat_to_clipped_ascii_bar_chart(&json_stat, BAR_CHART_WIDTH, line_width())
+        }
+    };
+}
+
+// the width of the terminal, else the COLUMNS variable, like when the output is piped to a pager
+fn line_width() -> usize {
+    if let Some((Width(width), _)) = terminal_size() {
+        return width as usize;
+    }
+    return env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(DEFAULT_LINE_WIDTH);
+}
+
+// the component is named after the file, without its extensions
+fn component_name(cli_options: &CliOptions) -> String {
+    return cli_options
+        .file_name
+        .as_ref()
+        .and_then(|file_name| Path::new(file_name).file_name()?.to_str())
+        .and_then(|file_name| file_name.split('.').next())
+        .filter(|component_name| !component_name.is_empty())
+        .unwrap_or(DEFAULT_COMPONENT_NAME)
+        .to_string();
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonstat::json_stat_extractor::extract_stat_from_json_value;
+    use serde_json::json;
+
+    use crate::cli::{parse_cli_options, CliOptions};
+    use crate::{
+        extract_json_stat, extract_sampled_files_stat, render_delta, render_groups,
+        render_item_stats, render_output, sample_file_names, try_to_pretty_json,
+        write_document_stats, write_heatmap, write_reports,
+    };
+    use jsonstat::json_stat_error::JsonStatError;
+    use jsonstat::json_stat_extractor::{
+        extract_item_stats_from_json, try_extract_document_stats_from_ndjson_with_options,
+        JsonStatOptions,
+    };
+    use jsonstat::limited_reader::LimitedReader;
+    use std::collections::HashMap;
+
+    fn cli_options(args: &[&str]) -> CliOptions {
+        let args = ["jsonstat"].iter().chain(args).map(|arg| arg.to_string());
+        return parse_cli_options(args).unwrap();
+    }
+
+    fn render(args: &[&str], value: serde_json::Value) -> String {
+        return render_output(extract_stat_from_json_value(value), &cli_options(args));
+    }
+
+    #[test]
+    fn it_should_apply_the_extraction_options_to_every_input_mode() {
+        let json_content = r#"[{"name":"abc"},{"nick":"de"}]"#;
+        let ndjson_content = "{\"name\":\"abc\"}\n{\"nick\":\"de\"}\n";
+        [
+            (vec!["--as-array"], json_content),
+            (vec!["--jsonc"], json_content),
+            (vec!["--concatenated"], ndjson_content),
+            (vec!["--ndjson"], ndjson_content),
+            (vec!["--jq-path", "."], json_content),
+        ]
+        .iter()
+        .for_each(|(mode_args, json_content)| {
+            let mut args = mode_args.clone();
+            args.extend(["--rename", "nick=name", "--skip-values", "--keys-only"]);
+            let cli_options = cli_options(&args);
+            let json_stat = extract_json_stat(json_content.as_bytes(), &cli_options).unwrap();
+            assert_eq!(render_output(json_stat, &cli_options), "name\n");
+        });
+    }
+
+    #[test]
+    fn it_should_report_a_truncated_input_in_every_input_mode() {
+        let json_content = r#"[{"name":"abc"},{"nick":"de"}]"#;
+        let ndjson_content = "{\"name\":\"abc\"}\n{\"nick\":\"de\"}\n";
+        [
+            (vec![], json_content),
+            (vec!["--as-array"], json_content),
+            (vec!["--jsonc"], json_content),
+            (vec!["--ndjson"], ndjson_content),
+            (vec!["--jq-path", "."], json_content),
+        ]
+        .iter()
+        .for_each(|(mode_args, json_content)| {
+            let cli_options = cli_options(mode_args);
+            let json_content_reader = LimitedReader::new(json_content.as_bytes(), 20);
+            let result = extract_json_stat(json_content_reader, &cli_options);
+            assert!(matches!(result, Err(JsonStatError::PartialInput(_))));
+        });
+    }
+
+    #[test]
+    fn it_should_extract_single_object_as_array() {
+        let cli_options = cli_options(&["--as-array"]);
+        let json_stat =
+            extract_json_stat(r#"{"a":1,"b":"test"}"#.as_bytes(), &cli_options).unwrap();
+        let output = render_output(json_stat, &cli_options);
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(output["ArrayStat"]["count"], 1);
+        assert_eq!(
+            output["ArrayStat"]["attributes"].as_array().unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn it_should_print_attribute_names_one_per_line() {
+        let output = render(&["--keys-only"], json!({"b": {"c": 1}, "a": [1, 2]}));
+        if cfg!(feature = "preserve_order") {
+            assert_eq!(output, "b\na\n");
+        } else {
+            assert_eq!(output, "a\nb\n");
+        }
+    }
+
+    #[test]
+    fn it_should_print_flattened_attribute_paths_one_per_line() {
+        let output = render(
+            &["--keys-only", "--flatten"],
+            json!({"b": {"c": 1}, "a": [{"d": 1}]}),
+        );
+        assert_eq!(output, "a\na[].d\nb\nb.c\n");
+    }
+
+    #[test]
+    fn it_should_output_only_attributes_above_min_bytes() {
+        let output = render(
+            &["--keys-only", "--min-bytes", "10"],
+            json!({"large": "0123456789012345678", "tiny": 1}),
+        );
+        assert_eq!(output, "large\n");
+    }
+
+    #[test]
+    fn it_should_output_one_stat_per_item_without_aggregation() {
+        let cli_options = cli_options(&["--no-aggregate"]);
+        let item_stats = extract_item_stats_from_json(r#"[{"a":1}, {"a":22}, {"b":3}]"#.as_bytes());
+        let output = render_item_stats(item_stats, &cli_options);
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let output = output.as_array().unwrap();
+        assert_eq!(output.len(), 3);
+        assert_eq!(output[0]["ObjStat"]["size"], 7);
+        assert_eq!(output[1]["ObjStat"]["size"], 8);
+        assert_eq!(output[2]["ObjStat"]["attributes"][0]["name"], "b");
+    }
+
+    #[test]
+    fn it_should_output_the_same_seeded_sample_twice() {
+        let json_content = serde_json::to_string(
+            &(0..200)
+                .map(|id| json!({ "id": id, "name": "x".repeat(id % 17) }))
+                .collect::<Vec<serde_json::Value>>(),
+        )
+        .unwrap();
+        let cli_options = cli_options(&["--sample", "20", "--sample-seed", "3"]);
+        let first_output = render_output(
+            extract_json_stat(json_content.as_bytes(), &cli_options).unwrap(),
+            &cli_options,
+        );
+        let second_output = render_output(
+            extract_json_stat(json_content.as_bytes(), &cli_options).unwrap(),
+            &cli_options,
+        );
+        assert_eq!(first_output, second_output);
+    }
+
+    #[test]
+    fn it_should_print_only_the_summary() {
+        let output = render(&["--summarize"], json!([{"a": 1}, {"a": 2}, {"b": 3}]));
+        assert!(output.starts_with("Array: 3 items"));
+        assert_eq!(output.lines().count(), 1);
+        let output = render(&["--summarize"], json!({"a": 1}));
+        assert!(output.starts_with("Object: 1 fields"));
+    }
+
+    #[test]
+    fn it_should_color_text_output_only_when_asked() {
+        let value = json!({"a": 1, "b": [1, 2]});
+        let output = render(&["--summarize", "--color", "never"], value.clone());
+        assert!(!output.contains('\u{1b}'));
+        let output = render(&["--keys-only", "--color", "never"], value.clone());
+        assert!(!output.contains('\u{1b}'));
+        let output = render(&["--summarize", "--color", "always"], value.clone());
+        assert!(output.contains('\u{1b}'));
+        let output = render(&["--keys-only", "--color", "always"], value.clone());
+        assert!(output.contains('\u{1b}'));
+        let output = render(&["--color", "always"], value);
+        assert!(!output.contains('\u{1b}'));
+    }
+
+    #[test]
+    fn it_should_output_renamed_attributes() {
+        let output = render(
+            &[
+                "--keys-only",
+                "--rename-attribute",
+                "a=x",
+                "--rename-attribute",
+                "x=y",
+            ],
+            json!({"a": 1, "b": 2}),
+        );
+        assert_eq!(output, "y\nb\n");
+    }
+
+    #[test]
+    fn it_should_output_attribute_names_without_values() {
+        let cli_options = cli_options(&["--skip-values"]);
+        let json_stat =
+            extract_json_stat(r#"{"token":"s3cr3t","id":42}"#.as_bytes(), &cli_options).unwrap();
+        let output = render_output(json_stat, &cli_options);
+        assert!(output.contains("\"token\""));
+        assert!(!output.contains("s3cr3t"));
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        output["ObjStat"]["attributes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .for_each(|attribute| assert_eq!(attribute["size"], 0));
+    }
+
+    #[test]
+    fn it_should_output_without_omitted_attributes() {
+        let output = render(
+            &[
+                "--keys-only",
+                "--omit-attribute",
+                "a",
+                "--omit-attribute",
+                "c",
+            ],
+            json!({"a": 1, "b": 2, "c": 3}),
+        );
+        assert_eq!(output, "b\n");
+    }
+
+    #[test]
+    fn it_should_output_deltas_against_a_saved_baseline() {
+        let baseline_output = render(&[], json!({"a": 1, "b": "x"}));
+        let baseline_stat = serde_json::from_str(&baseline_output).unwrap();
+        let json_stat = extract_stat_from_json_value(json!({"a": 1, "b": "xyz"}));
+        let output = render_delta(baseline_stat, json_stat, &cli_options(&[]));
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(output["delta"], 2);
+        assert_eq!(output["attributes"][1]["name"], "b");
+        assert_eq!(output["attributes"][1]["change"], "changed");
+        assert_eq!(output["attributes"][1]["delta"], 2);
+    }
+
+    #[test]
+    fn it_should_hash_the_attribute_names_of_the_deltas() {
+        let baseline_stat = extract_stat_from_json_value(json!({"secret_name": "x"}));
+        let json_stat = extract_stat_from_json_value(json!({"secret_name": "xyz"}));
+        let output = render_delta(baseline_stat, json_stat, &cli_options(&["--hash-names"]));
+        assert!(!output.contains("secret_name"));
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(output["attributes"].as_array().unwrap().len(), 1);
+        assert_eq!(output["attributes"][0]["change"], "changed");
+    }
+
+    #[test]
+    fn it_should_output_hashed_attribute_names() {
+        let output = render(&["--keys-only", "--hash-names"], json!({"id": 1}));
+        assert_eq!(output, "a5614527\n");
+    }
+
+    #[test]
+    fn it_should_extract_jsonc_content() {
+        let cli_options = cli_options(&["--jsonc", "--keys-only"]);
+        let json_stat = extract_json_stat(
+            "{\n  // comment\n  \"a\": 1 /* other comment */\n}".as_bytes(),
+            &cli_options,
+        )
+        .unwrap();
+        assert_eq!(render_output(json_stat, &cli_options), "a\n");
+    }
+
+    #[test]
+    fn it_should_extract_the_sub_document_selected_by_jq_path() {
+        let cli_options = cli_options(&["--jq-path", ".items[] | .user", "--keys-only"]);
+        let json_stat = extract_json_stat(
+            r#"{"id":1,"items":[{"user":{"name":"a"}},{"user":{"name":"b"}}]}"#.as_bytes(),
+            &cli_options,
+        )
+        .unwrap();
+        assert_eq!(render_output(json_stat, &cli_options), "name\n");
+    }
+
+    #[test]
+    fn it_should_output_empty_attributes_only_when_included() {
+        let json_value = json!({"comment": "", "id": 1});
+        assert_eq!(render(&["--keys-only"], json_value.clone()), "id\n");
+        assert_eq!(
+            render(&["--keys-only", "--include-empty"], json_value),
+            "comment\nid\n"
+        );
+    }
+
+    #[test]
+    fn it_should_print_indexed_paths_of_array_items() {
+        let cli_options = cli_options(&["--keys-only", "--flatten-arrays"]);
+        let json_stat =
+            extract_json_stat(r#"{"items":["a","b"]}"#.as_bytes(), &cli_options).unwrap();
+        assert_eq!(
+            render_output(json_stat, &cli_options),
+            "items\nitems[0]\nitems[1]\n"
+        );
+    }
+
+    #[test]
+    fn it_should_report_a_serialization_error_instead_of_panicking() {
+        let value_by_pair: HashMap<(u8, u8), u8> = HashMap::from([((1, 2), 3)]);
+        assert!(matches!(
+            try_to_pretty_json(&value_by_pair),
+            Err(JsonStatError::Serialization(_))
+        ));
+        assert_eq!(try_to_pretty_json(&[1]).unwrap(), "[\n  1\n]\n");
+    }
+
+    #[test]
+    fn it_should_extract_ndjson_content() {
+        let cli_options = cli_options(&["--ndjson", "--keys-only"]);
+        let json_stat =
+            extract_json_stat("{\"a\":1}\n{\"a\":2}\n".as_bytes(), &cli_options).unwrap();
+        assert_eq!(render_output(json_stat, &cli_options), "a\n");
+    }
+
+    #[test]
+    fn it_should_output_normalized_attribute_order() {
+        let output = render(&["--keys-only", "--normalize"], json!({"b": 1, "a": 2}));
+        assert_eq!(output, "a\nb\n");
+    }
+
+    #[test]
+    fn it_should_output_one_stat_record_per_ndjson_document() {
+        let cli_options = cli_options(&["--ndjson", "--per-document"]);
+        let options = JsonStatOptions::default();
+        let document_stats = try_extract_document_stats_from_ndjson_with_options(
+            "{\"a\":1}\n\n{\"bb\":[2]}\n".as_bytes(),
+            &options,
+        );
+        let mut output: Vec<u8> = vec![];
+        write_document_stats(document_stats, &cli_options, &mut output).unwrap();
+        let records: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["ObjStat"]["attributes"][0]["name"], "a");
+        assert_eq!(records[1]["ObjStat"]["attributes"][0]["name"], "bb");
+    }
+
+    #[test]
+    fn it_should_write_the_document_stats_up_to_the_first_invalid_line() {
+        let cli_options =
+            cli_options(&["--ndjson", "--per-document", "--rename-attribute", "a=id"]);
+        let options = JsonStatOptions::default();
+        let document_stats = try_extract_document_stats_from_ndjson_with_options(
+            "{\"a\":1}\n{\"a\":}\n{\"a\":2}\n".as_bytes(),
+            &options,
+        );
+        let mut output: Vec<u8> = vec![];
+        let result = write_document_stats(document_stats, &cli_options, &mut output);
+        assert!(
+            matches!(result, Err(JsonStatError::InvalidJson(message)) if message.starts_with("line 2: "))
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.contains("\"name\":\"id\""));
+    }
+
+    #[test]
+    fn it_should_output_the_attribute_paths_on_demand() {
+        let value = json!({"user": {"name": "a"}});
+        let output: serde_json::Value =
+            serde_json::from_str(&render(&["--include-path-stats"], value.clone())).unwrap();
+        let user_attr_stat = &output["ObjStat"]["attributes"][0];
+        assert_eq!(user_attr_stat["path"], "/user");
+        assert_eq!(
+            user_attr_stat["values"][0]["ObjStat"]["attributes"][0]["path"],
+            "/user/name"
+        );
+        let output: serde_json::Value = serde_json::from_str(&render(&[], value)).unwrap();
+        assert!(output["ObjStat"]["attributes"][0].get("path").is_none());
+    }
+
+    #[test]
+    fn it_should_output_the_stats_grouped_by_key_value() {
+        let cli_options = cli_options(&["--group-by", "t"]);
+        let value = json!([{"t":"a","x":1},{"t":"a","x":22},{"t":"b","x":3}]);
+        let output = render_groups(value, "t", &cli_options);
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(output.as_object().unwrap().len(), 2);
+        assert_eq!(output["\"a\""]["ArrayStat"]["count"], 2);
+        assert_eq!(output["\"b\""]["ArrayStat"]["count"], 1);
+    }
+
+    #[test]
+    fn it_should_apply_the_cli_options_to_every_group() {
+        let cli_options = cli_options(&[
+            "--group-by",
+            "t",
+            "--skip-values",
+            "--rename-attribute",
+            "x=size",
+        ]);
+        let value = json!([{"t":"a","x":1},{"t":"a","x":22},{"t":"b","x":3}]);
+        let output = render_groups(value, "t", &cli_options);
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        ["\"a\"", "\"b\""].iter().for_each(|group| {
+            let attributes = &output[group]["ArrayStat"]["attributes"];
+            assert_eq!(attributes[1]["name"], "size");
+            assert_eq!(attributes[1]["size"], 0);
+        });
+    }
+
+    #[test]
+    fn it_should_cap_the_string_sizes_on_demand() {
+        let cli_options = cli_options(&["--cap-string", "100"]);
+        let document = json!({"text": "x".repeat(1000)}).to_string();
+        let json_stat = extract_json_stat(document.as_bytes(), &cli_options).unwrap();
+        let output = render_output(json_stat, &cli_options);
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let text_attr_stat = &output["ObjStat"]["attributes"][0];
+        assert_eq!(text_attr_stat["size"], 102);
+        assert_eq!(text_attr_stat["string_truncated_count"], 1);
+    }
+
+    #[test]
+    fn it_should_output_an_openapi_component_named_after_the_file() {
+        let cli_options = cli_options(&["--format", "openapi-component", "data/users.json.gz"]);
+        let json_stat = extract_stat_from_json_value(json!({"id": 1}));
+        let output: serde_json::Value =
+            serde_json::from_str(&render_output(json_stat, &cli_options)).unwrap();
+        assert_eq!(output["type"], "object");
+        assert_eq!(output["title"], "users");
+        assert_eq!(output["properties"]["id"]["type"], "number");
+    }
+
+    #[test]
+    fn it_should_output_a_schema_registry_request_named_after_the_file() {
+        let cli_options = cli_options(&["--format", "confluent-avro", "users.json"]);
+        let json_stat = extract_stat_from_json_value(json!({"id": 1}));
+        let output: serde_json::Value =
+            serde_json::from_str(&render_output(json_stat, &cli_options)).unwrap();
+        let schema: serde_json::Value =
+            serde_json::from_str(output["schema"].as_str().unwrap()).unwrap();
+        assert_eq!(schema["name"], "users");
+        assert_eq!(schema["fields"][0]["type"], "double");
+    }
+
+    #[test]
+    fn it_should_write_one_report_per_input_file() {
+        let work_dir =
+            std::env::temp_dir().join(format!("jsonstat-reports-{}", std::process::id()));
+        let input_dir = work_dir.join("other");
+        std::fs::create_dir_all(&input_dir).unwrap();
+        let users_file = work_dir.join("users.json");
+        let other_users_file = input_dir.join("users.json");
+        let events_file = work_dir.join("events.json");
+        std::fs::write(&users_file, r#"{"id":1}"#).unwrap();
+        std::fs::write(&other_users_file, r#"{"id":2}"#).unwrap();
+        std::fs::write(&events_file, r#"[{"type":"click"}]"#).unwrap();
+        let output_dir = work_dir.join("reports");
+        let output_dir = output_dir.to_str().unwrap();
+        let file_names = [&users_file, &events_file, &other_users_file]
+            .map(|file_name| file_name.to_str().unwrap().to_string());
+        let mut args = vec!["--output-dir", output_dir];
+        args.extend(file_names.iter().map(String::as_str));
+        let report_paths = write_reports(output_dir, &cli_options(&args)).unwrap();
+        let report_names: Vec<String> = report_paths
+            .iter()
+            .map(|report_path| {
+                report_path
+                    .file_name()
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            report_names,
+            vec![
+                "users.stats.json",
+                "events.stats.json",
+                "users-2.stats.json"
+            ]
+        );
+        let events_report = std::fs::read_to_string(&report_paths[1]).unwrap();
+        let events_report: serde_json::Value = serde_json::from_str(&events_report).unwrap();
+        assert_eq!(events_report["ArrayStat"]["count"], 1);
+        std::fs::remove_dir_all(work_dir).unwrap();
+    }
+
+    #[test]
+    fn it_should_write_a_heatmap_row_per_input_file() {
+        let work_dir =
+            std::env::temp_dir().join(format!("jsonstat-heatmap-{}", std::process::id()));
+        std::fs::create_dir_all(&work_dir).unwrap();
+        let morning_file = work_dir.join("morning.json");
+        let evening_file = work_dir.join("evening.json");
+        std::fs::write(&morning_file, r#"{"id":1,"name":"abc"}"#).unwrap();
+        std::fs::write(&evening_file, r#"{"id":12}"#).unwrap();
+        let heatmap_output = work_dir.join("heatmap.csv");
+        let heatmap_output = heatmap_output.to_str().unwrap();
+        let morning_file = morning_file.to_str().unwrap();
+        let evening_file = evening_file.to_str().unwrap();
+        let cli_options = cli_options(&[
+            "--heatmap-output",
+            heatmap_output,
+            morning_file,
+            evening_file,
+        ]);
+        write_heatmap(heatmap_output, &cli_options).unwrap();
+        let heatmap_csv = std::fs::read_to_string(heatmap_output).unwrap();
+        let rows: Vec<Vec<&str>> = heatmap_csv
+            .lines()
+            .map(|row| row.split(',').collect())
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|row| row.len() == 3));
+        assert_eq!(rows[0], vec!["label", "id", "name"]);
+        assert_eq!(rows[2], vec![evening_file, "2", ""]);
+        std::fs::remove_dir_all(work_dir).unwrap();
+    }
+
+    #[test]
+    fn it_should_profile_a_reproducible_sample_of_the_files() {
+        let work_dir =
+            std::env::temp_dir().join(format!("jsonstat-sample-files-{}", std::process::id()));
+        let nested_dir = work_dir.join("nested");
+        std::fs::create_dir_all(&nested_dir).unwrap();
+        (0..6).for_each(|index| {
+            let file_dir = if index % 2 == 0 {
+                &work_dir
+            } else {
+                &nested_dir
+            };
+            std::fs::write(file_dir.join(format!("{index}.json")), r#"{"id":1}"#).unwrap();
+        });
+        let work_dir_name = work_dir.to_str().unwrap();
+        let cli_options =
+            cli_options(&["--sample-files", "4", "--sample-seed", "3", work_dir_name]);
+        let file_names = sample_file_names(4, &cli_options).unwrap();
+        assert_eq!(file_names.len(), 4);
+        assert_eq!(file_names, sample_file_names(4, &cli_options).unwrap());
+        let json_stat = extract_sampled_files_stat(4, &cli_options).unwrap();
+        let output: serde_json::Value =
+            serde_json::from_str(&render_output(json_stat, &cli_options)).unwrap();
+        assert_eq!(output["ObjStat"]["count"], 4);
+        std::fs::remove_dir_all(work_dir).unwrap();
+    }
 }
@@ -0,0 +1,516 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::json_stat_extractor::JsonStat::{ArrayStat, ObjStat, ValStat};
+use crate::json_stat_extractor::{
+    json_stat_attributes, json_stat_size, mean_item_size, JsonAttrStat, JsonStat,
+};
+
+// nearest-rank percentile, p going from 0.0 to 1.0: the smallest size such that at least p of the
+// sizes are lower or equal, 0 for no size. The sizes are sorted on each call, which is
+// O(n log n), so several percentiles of the same sizes are better computed on sorted sizes.
+pub fn json_stat_percentile(sizes: &[usize], p: f64) -> usize {
+    let mut sorted_sizes = sizes.to_vec();
+    sorted_sizes.sort_unstable();
+    return sorted_percentile(&sorted_sizes, p);
+}
+
+pub(crate) fn sorted_percentile(sorted_sizes: &[usize], p: f64) -> usize {
+    if sorted_sizes.is_empty() {
+        return 0;
+    }
+    let rank = (p.clamp(0.0, 1.0) * sorted_sizes.len() as f64).ceil() as usize;
+    return sorted_sizes[rank.max(1) - 1];
+}
+
+// sample standard deviation, like pandas does, 0.0 for less than 2 sizes
+pub(crate) fn sample_std_dev(sizes: &[usize]) -> f64 {
+    if sizes.len() < 2 {
+        return 0.0;
+    }
+    let mean = sizes.iter().sum::<usize>() as f64 / sizes.len() as f64;
+    let squared_deviations: f64 = sizes.iter().map(|size| (*size as f64 - mean).powi(2)).sum();
+    return (squared_deviations / (sizes.len() - 1) as f64).sqrt();
+}
+
+// sample standard deviation of the union of two samples given by their count, mean and sample
+// standard deviation
+pub(crate) fn pooled_std_dev(sample: (usize, f64, f64), other_sample: (usize, f64, f64)) -> f64 {
+    let (count, mean, std_dev) = sample;
+    let (other_count, other_mean, other_std_dev) = other_sample;
+    let total_count = count + other_count;
+    if total_count < 2 {
+        return 0.0;
+    }
+    let total_mean = (mean * count as f64 + other_mean * other_count as f64) / total_count as f64;
+    let squared_deviations = count.saturating_sub(1) as f64 * std_dev.powi(2)
+        + other_count.saturating_sub(1) as f64 * other_std_dev.powi(2)
+        + count as f64 * (mean - total_mean).powi(2)
+        + other_count as f64 * (other_mean - total_mean).powi(2);
+    return (squared_deviations / (total_count - 1) as f64).sqrt();
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CdfPoint {
+    pub(crate) size: usize,
+    /// Share of the sizes lower or equal to size, from 0.0 to 1.0.
+    pub(crate) ratio: f64,
+}
+
+// the compact serialized sizes of the values found at a flattened path, like address.city or
+// orders[].amount, in each item of a root array or in the root value, the items missing the
+// attribute being left out
+pub fn attribute_value_sizes(json_value: &Value, path: &str) -> Vec<usize> {
+    let segments: Vec<&str> = path.split('.').collect();
+    let mut values = vec![];
+    match json_value {
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_path_values(item, &segments, &mut values)),
+        json_value => collect_path_values(json_value, &segments, &mut values),
+    }
+    return values
+        .iter()
+        .map(|value| serde_json::to_string(value).map_or(0, |text| text.len()))
+        .collect();
+}
+
+fn collect_path_values<'a>(json_value: &'a Value, segments: &[&str], values: &mut Vec<&'a Value>) {
+    let Some((segment, next_segments)) = segments.split_first() else {
+        values.push(json_value);
+        return;
+    };
+    let (name, through_items) = match segment.strip_suffix("[]") {
+        Some(name) => (name, true),
+        None => (*segment, false),
+    };
+    match (json_value.get(name), through_items) {
+        (Some(Value::Array(items)), true) => items
+            .iter()
+            .for_each(|item| collect_path_values(item, next_segments, values)),
+        (Some(attr_value), false) => collect_path_values(attr_value, next_segments, values),
+        _ => {}
+    }
+}
+
+// the empirical cumulative distribution of the sizes downsampled to at most points points evenly
+// spread over the sorted sizes, the first point being the min size and the last one the max size
+// with a ratio of 1.0, the ratio of a size being the share of the sizes lower than or equal to it
+pub fn size_cdf(sizes: &[usize], points: usize) -> Vec<CdfPoint> {
+    let mut sorted_sizes = sizes.to_vec();
+    sorted_sizes.sort_unstable();
+    let size_count = sorted_sizes.len();
+    let point_count = points.min(size_count);
+    return (0..point_count)
+        .map(|point| {
+            let index = match point_count {
+                1 => size_count - 1,
+                _ => point * (size_count - 1) / (point_count - 1),
+            };
+            let size = sorted_sizes[index];
+            let lower_or_equal_count =
+                sorted_sizes.partition_point(|other_size| *other_size <= size);
+            return CdfPoint {
+                size,
+                ratio: lower_or_equal_count as f64 / size_count as f64,
+            };
+        })
+        .collect();
+}
+
+struct SizeDescription {
+    count: usize,
+    total_size: usize,
+    mean_size: f64,
+    std_size: f64,
+    min_size: usize,
+    p25_size: usize,
+    p50_size: usize,
+    p75_size: usize,
+    max_size: usize,
+}
+
+impl SizeDescription {
+    // the exact description of known sizes
+    fn from_sizes(sizes: &[usize]) -> Self {
+        let mut sorted_sizes = sizes.to_vec();
+        sorted_sizes.sort_unstable();
+        let total_size: usize = sorted_sizes.iter().sum();
+        SizeDescription {
+            count: sorted_sizes.len(),
+            total_size,
+            mean_size: total_size as f64 / sorted_sizes.len().max(1) as f64,
+            std_size: sample_std_dev(&sorted_sizes),
+            min_size: sorted_sizes.first().copied().unwrap_or(0),
+            p25_size: sorted_percentile(&sorted_sizes, 0.25),
+            p50_size: sorted_percentile(&sorted_sizes, 0.5),
+            p75_size: sorted_percentile(&sorted_sizes, 0.75),
+            max_size: sorted_sizes.last().copied().unwrap_or(0),
+        }
+    }
+
+    // the sizes of the occurrences are not kept, the quartiles are given by the mean size
+    fn from_mean(count: usize, mean_size: usize, min_size: usize, max_size: usize) -> Self {
+        SizeDescription {
+            count,
+            total_size: mean_size * count,
+            mean_size: mean_size as f64,
+            std_size: 0.0,
+            min_size,
+            p25_size: mean_size,
+            p50_size: mean_size,
+            p75_size: mean_size,
+            max_size,
+        }
+    }
+
+    fn into_map(self) -> BTreeMap<String, Value> {
+        return BTreeMap::from([
+            ("count".to_string(), json!(self.count)),
+            ("total_size".to_string(), json!(self.total_size)),
+            ("mean_size".to_string(), json!(self.mean_size)),
+            ("std_size".to_string(), json!(self.std_size)),
+            ("min_size".to_string(), json!(self.min_size)),
+            ("25%".to_string(), json!(self.p25_size)),
+            ("50%".to_string(), json!(self.p50_size)),
+            ("75%".to_string(), json!(self.p75_size)),
+            ("max_size".to_string(), json!(self.max_size)),
+        ]);
+    }
+}
+
+// a pandas like description of the sizes, the ones of the items for an array and the ones of the
+// values for each attribute under "attributes". An object or a scalar stat is described by its
+// mean size, its quartiles being the mean size and its standard deviation 0.0, like an attribute
+// built without its values.
+pub fn describe(stat: &JsonStat) -> BTreeMap<String, Value> {
+    let size_description = match stat {
+        ArrayStat(array_stat) => SizeDescription {
+            count: array_stat.count,
+            total_size: (mean_item_size(array_stat) * array_stat.count as f64).round() as usize,
+            mean_size: mean_item_size(array_stat),
+            std_size: array_stat.std_size,
+            min_size: array_stat.min_size,
+            p25_size: array_stat.p25_size,
+            p50_size: array_stat.p50_size,
+            p75_size: array_stat.p75_size,
+            max_size: array_stat.max_size,
+        },
+        ObjStat(obj_stat) => SizeDescription::from_mean(
+            obj_stat.count,
+            obj_stat.size,
+            obj_stat.min_size,
+            obj_stat.max_size,
+        ),
+        ValStat(val_stat) => {
+            SizeDescription::from_mean(1, val_stat.size, val_stat.min_size, val_stat.max_size)
+        }
+    };
+    let attribute_descriptions: Map<String, Value> = json_stat_attributes(stat)
+        .iter()
+        .map(|attr_stat| {
+            let attribute_description = attribute_size_description(attr_stat).into_map();
+            (attr_stat.name.clone(), json!(attribute_description))
+        })
+        .collect();
+    let mut description = size_description.into_map();
+    description.insert(
+        "attributes".to_string(),
+        Value::Object(attribute_descriptions),
+    );
+    return description;
+}
+
+fn attribute_size_description(attr_stat: &JsonAttrStat) -> SizeDescription {
+    if attr_stat.values.is_empty() {
+        return SizeDescription::from_mean(
+            attr_stat.count,
+            attr_stat.size,
+            attr_stat.min_size,
+            attr_stat.max_size,
+        );
+    }
+    let value_sizes: Vec<usize> = attr_stat.values.iter().map(json_stat_size).collect();
+    return SizeDescription::from_sizes(&value_sizes);
+}
+
+// whether the values of an attribute of the stat are in non decreasing order, the values being
+// kept with `JsonStatOptions::track_order`, `None` when the attribute is missing, when its values
+// were not kept or are not all numbers or all strings
+pub fn is_sorted_by(stat: &JsonStat, attr: &str) -> Option<bool> {
+    let attr_stat = json_stat_attributes(stat)
+        .iter()
+        .find(|attr_stat| attr_stat.name == attr)?;
+    let ordered_values = attr_stat.ordered_values.as_ref()?;
+    if let Some(numbers) = ordered_values
+        .iter()
+        .map(Value::as_f64)
+        .collect::<Option<Vec<f64>>>()
+    {
+        return Some(numbers.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+    let texts = ordered_values
+        .iter()
+        .map(Value::as_str)
+        .collect::<Option<Vec<&str>>>()?;
+    return Some(texts.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+const PRIMARY_KEY_NAMES: [&str; 3] = ["id", "uuid", "key"];
+
+// the first attribute of an array of objects present in every item with distinct string values or
+// distinct integer values, an attribute named id, uuid or key being preferred, the values being
+// told apart with the value frequencies, which are not limited by
+// `JsonStatOptions::exact_distinct_threshold` unlike `unique_value_count`
+pub fn infer_primary_key(stat: &JsonStat) -> Option<String> {
+    let ArrayStat(array_stat) = stat else {
+        return None;
+    };
+    let candidates: Vec<&JsonAttrStat> = array_stat
+        .attributes
+        .iter()
+        .filter(|attr_stat| {
+            attr_stat.count == array_stat.count
+                && has_distinct_values(attr_stat)
+                && has_key_type(attr_stat)
+        })
+        .collect();
+    return candidates
+        .iter()
+        .find(|attr_stat| PRIMARY_KEY_NAMES.contains(&attr_stat.name.as_str()))
+        .or(candidates.first())
+        .map(|attr_stat| attr_stat.name.clone());
+}
+
+fn has_distinct_values(attr_stat: &JsonAttrStat) -> bool {
+    return attr_stat.value_frequencies.len() == attr_stat.count
+        && attr_stat
+            .value_frequencies
+            .values()
+            .all(|count| *count == 1);
+}
+
+fn has_key_type(attr_stat: &JsonAttrStat) -> bool {
+    let type_names: Vec<&str> = attr_stat.type_counts.keys().map(String::as_str).collect();
+    return match type_names.as_slice() {
+        ["string"] => true,
+        ["number"] => attr_stat
+            .value_frequencies
+            .keys()
+            .all(|(value, _)| value.parse::<i64>().is_ok()),
+        _ => false,
+    };
+}
+
+// estimated share of the items having all the attributes of the stat, the absences of the
+// attributes being assumed independent, that is the product of their present ratios, 1.0 for a
+// stat without attributes
+pub fn json_stat_attribute_coverage(stat: &JsonStat) -> f64 {
+    return json_stat_attributes(stat)
+        .iter()
+        .map(|attr_stat| attr_stat.present_ratio)
+        .product();
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::json_stat_extractor::{
+        extract_stat_from_json_value, extract_stat_from_json_with_options, json_stat_attributes,
+        JsonStat, JsonStatOptions,
+    };
+    use crate::stats_math::{
+        attribute_value_sizes, describe, infer_primary_key, is_sorted_by,
+        json_stat_attribute_coverage, json_stat_percentile, size_cdf,
+    };
+
+    #[test]
+    fn it_should_provide_the_only_size_whatever_the_percentile() {
+        assert_eq!(json_stat_percentile(&[7], 0.0), 7);
+        assert_eq!(json_stat_percentile(&[7], 0.5), 7);
+        assert_eq!(json_stat_percentile(&[7], 1.0), 7);
+    }
+
+    #[test]
+    fn it_should_provide_nearest_rank_of_two_sizes() {
+        assert_eq!(json_stat_percentile(&[9, 3], 0.5), 3);
+        assert_eq!(json_stat_percentile(&[9, 3], 0.51), 9);
+    }
+
+    #[test]
+    fn it_should_provide_min_and_max_sizes_at_bounds() {
+        let sizes = [5, 1, 4, 2, 3];
+        assert_eq!(json_stat_percentile(&sizes, 0.0), 1);
+        assert_eq!(json_stat_percentile(&sizes, 1.0), 5);
+        assert_eq!(json_stat_percentile(&[], 0.5), 0);
+    }
+
+    #[test]
+    fn it_should_provide_full_coverage_when_all_attributes_are_present() {
+        let stat = extract_stat_from_json_value(json!([{"a": 1, "b": 2}, {"a": 3, "b": 4}]));
+        assert_eq!(json_stat_attribute_coverage(&stat), 1.0);
+        let stat = extract_stat_from_json_value(json!("no attributes"));
+        assert_eq!(json_stat_attribute_coverage(&stat), 1.0);
+    }
+
+    #[test]
+    fn it_should_multiply_the_present_ratios_of_the_attributes() {
+        let stat = extract_stat_from_json_value(json!([
+            {"a": 1, "b": 2},
+            {"a": 3},
+            {"b": 4},
+            {"a": 5, "b": 6}
+        ]));
+        assert_eq!(json_stat_attribute_coverage(&stat), 0.5625);
+    }
+
+    #[test]
+    fn it_should_describe_the_item_and_attribute_sizes() {
+        let stat = extract_stat_from_json_value(json!([
+            {"name": "a"},
+            {"name": "abc", "tags": [1]},
+            {"name": "abcdefgh"}
+        ]));
+        let description = describe(&stat);
+        let expected_keys = [
+            "count",
+            "total_size",
+            "mean_size",
+            "std_size",
+            "min_size",
+            "25%",
+            "50%",
+            "75%",
+            "max_size",
+        ];
+        expected_keys.iter().for_each(|key| {
+            assert!(description[*key].as_f64().unwrap() >= 0.0, "{key}");
+        });
+        assert_eq!(description["count"], 3);
+        assert_eq!(description["total_size"], 56);
+        assert_eq!(description["min_size"], 12);
+        assert_eq!(description["max_size"], 25);
+        let name_description = &description["attributes"]["name"];
+        expected_keys.iter().for_each(|key| {
+            assert!(name_description[*key].as_f64().unwrap() >= 0.0, "{key}");
+        });
+        assert_eq!(name_description["count"], 3);
+        assert_eq!(name_description["50%"], 5);
+        assert_eq!(name_description["std_size"], 3.605551275463989);
+        assert_eq!(description["attributes"]["tags"]["count"], 1);
+    }
+
+    fn ordered_stat(json_content: &str) -> JsonStat {
+        let options = JsonStatOptions {
+            track_order: true,
+            ..JsonStatOptions::default()
+        };
+        return extract_stat_from_json_with_options(json_content.as_bytes(), &options);
+    }
+
+    #[test]
+    fn it_should_tell_whether_the_items_are_sorted_by_an_attribute() {
+        let stat = ordered_stat(r#"[{"ts": 1, "id": "b"}, {"ts": 1}, {"ts": 3.5, "id": "a"}]"#);
+        assert_eq!(is_sorted_by(&stat, "ts"), Some(true));
+        assert_eq!(is_sorted_by(&stat, "id"), Some(false));
+        let stat = ordered_stat(r#"[{"id": "a"}, {"id": "ab"}, {"id": "b"}]"#);
+        assert_eq!(is_sorted_by(&stat, "id"), Some(true));
+    }
+
+    #[test]
+    fn it_should_not_tell_the_order_of_untracked_or_mixed_values() {
+        let stat = ordered_stat(r#"[{"ts": 1, "mixed": 1}, {"ts": 2, "mixed": "2"}]"#);
+        assert_eq!(is_sorted_by(&stat, "mixed"), None);
+        assert_eq!(is_sorted_by(&stat, "missing"), None);
+        let stat = extract_stat_from_json_value(json!([{"ts": 1}, {"ts": 2}]));
+        assert_eq!(is_sorted_by(&stat, "ts"), None);
+    }
+
+    #[test]
+    fn it_should_infer_the_primary_key_preferring_usual_names() {
+        let stat = extract_stat_from_json_value(json!([
+            {"code": "a", "id": 1, "label": "x"},
+            {"code": "b", "id": 2, "label": "x"}
+        ]));
+        assert_eq!(infer_primary_key(&stat), Some("id".to_string()));
+        let stat = extract_stat_from_json_value(json!([
+            {"ratio": 0.5, "code": "a"},
+            {"ratio": 1.5, "code": "b"}
+        ]));
+        assert_eq!(infer_primary_key(&stat), Some("code".to_string()));
+    }
+
+    #[test]
+    fn it_should_not_infer_a_primary_key_without_unique_attribute() {
+        let stat = extract_stat_from_json_value(json!([
+            {"id": 1, "label": "x"},
+            {"id": 1, "label": "x"},
+            {"label": "y"}
+        ]));
+        assert_eq!(infer_primary_key(&stat), None);
+        let stat = extract_stat_from_json_value(json!({"id": 1}));
+        assert_eq!(infer_primary_key(&stat), None);
+    }
+
+    #[test]
+    fn it_should_infer_a_primary_key_above_the_exact_distinct_threshold() {
+        let items: Vec<_> = (0..101)
+            .map(|index| json!({"label": format!("item {}", index % 2), "uuid": format!("u{index}")}))
+            .collect();
+        let stat = extract_stat_from_json_value(json!(items));
+        assert_eq!(json_stat_attributes(&stat)[1].unique_value_count, None);
+        assert_eq!(infer_primary_key(&stat), Some("uuid".to_string()));
+        let mut items = items;
+        items.push(json!({"label": "item 0", "uuid": "u100"}));
+        let stat = extract_stat_from_json_value(json!(items));
+        assert_eq!(infer_primary_key(&stat), None);
+    }
+
+    #[test]
+    fn it_should_give_a_cdf_from_the_min_to_the_max_size() {
+        let items: Vec<_> = (1..=10)
+            .rev()
+            .map(|length| json!({"id": length, "user": {"name": "x".repeat(length)}}))
+            .collect();
+        let sizes = attribute_value_sizes(&json!(items), "user.name");
+        assert_eq!(sizes.len(), 10);
+        let cdf = size_cdf(&sizes, 4);
+        assert_eq!(cdf.len(), 4);
+        assert_eq!(cdf[0].size, 3);
+        assert_eq!(cdf[0].ratio, 0.1);
+        assert_eq!(cdf[3].size, 12);
+        assert_eq!(cdf[3].ratio, 1.0);
+        assert_eq!(size_cdf(&sizes, 50).len(), 10);
+        assert!(size_cdf(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn it_should_give_the_ratio_of_the_last_of_equal_sizes() {
+        let cdf = size_cdf(&[3, 3, 3, 3], 2);
+        assert_eq!(cdf.len(), 2);
+        assert!(cdf
+            .iter()
+            .all(|point| point.size == 3 && point.ratio == 1.0));
+        let cdf = size_cdf(&[1, 2, 2, 2, 5], 3);
+        assert_eq!(cdf[0].ratio, 0.2);
+        assert_eq!(cdf[1].size, 2);
+        assert_eq!(cdf[1].ratio, 0.8);
+        assert_eq!(cdf[2].ratio, 1.0);
+    }
+
+    #[test]
+    fn it_should_collect_the_sizes_through_nested_arrays() {
+        let json_value = json!([
+            {"orders": [{"amount": 12.5}, {"amount": 7}]},
+            {"orders": []},
+            {"id": 3}
+        ]);
+        assert_eq!(
+            attribute_value_sizes(&json_value, "orders[].amount"),
+            vec![4, 1]
+        );
+    }
+}